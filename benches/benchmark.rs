@@ -1,225 +1,112 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use std::fs;
-
-pub fn bench_day01(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day01.txt").unwrap();
-    c.bench_function("day01::max_total_calories", |b| {
-        b.iter(|| aoc22::day01::max_total_calories(&input))
-    });
-    c.bench_function("day01::max3_total_calories (BinHeap)", |b| {
-        b.iter(|| aoc22::day01::max3_total_calories(&input))
-    });
-}
-
-pub fn bench_day02(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day02.txt").unwrap();
-    c.bench_function("day02::total_score_p1", |b| {
-        b.iter(|| aoc22::day02::total_score_p1(&input))
-    });
-    c.bench_function("day02::total_score_p2", |b| {
-        b.iter(|| aoc22::day02::total_score_p2(&input))
-    });
-}
-
-pub fn bench_day03(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day03.txt").unwrap();
-    c.bench_function("day03::sum_priorities_p1", |b| {
-        b.iter(|| aoc22::day03::sum_priorities_p1(&input))
-    });
-    c.bench_function("day03::sum_priorities_p2", |b| {
-        b.iter(|| aoc22::day03::sum_priorities_p2(&input))
-    });
-}
-
-pub fn bench_day04(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day04.txt").unwrap();
-    c.bench_function("day04::num_fully_contained", |b| {
-        b.iter(|| aoc22::day04::num_fully_contained(&input))
-    });
-    c.bench_function("day04::num_overlapping", |b| {
-        b.iter(|| aoc22::day04::num_overlapping(&input))
-    });
-}
-
-pub fn bench_day05(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day05.txt").unwrap();
-    c.bench_function("day05::top_of_stack_p1", |b| {
-        b.iter(|| aoc22::day05::top_of_stack_p1(&input))
-    });
-    c.bench_function("day05::top_of_stack_p2", |b| {
-        b.iter(|| aoc22::day05::top_of_stack_p2(&input))
-    });
-}
-
-pub fn bench_day06(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day06.txt").unwrap();
-    c.bench_function("day06::start_of_packet_p1", |b| {
-        b.iter(|| aoc22::day06::start_of_packet_p1(&input))
-    });
-    c.bench_function("day06::start_of_packet_p2", |b| {
-        b.iter(|| aoc22::day06::start_of_packet_p2(&input))
-    });
-}
-
-pub fn bench_day07(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day07.txt").unwrap();
-    c.bench_function("day07::total_size_p1", |b| {
-        b.iter(|| aoc22::day07::total_size_p1(&input))
-    });
-    c.bench_function("day07::total_size_p2", |b| {
-        b.iter(|| aoc22::day07::total_size_p2(&input))
-    });
-}
-
-pub fn bench_day08(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day08.txt").unwrap();
-    c.bench_function("day08::num_visible_trees", |b| {
-        b.iter(|| aoc22::day08::num_visible_trees(&input))
-    });
-    c.bench_function("day08::highest_scenic_score", |b| {
-        b.iter(|| aoc22::day08::highest_scenic_score(&input))
-    });
-}
-
-pub fn bench_day09(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day09.txt").unwrap();
-    c.bench_function("day09::num_tail_positions_p1", |b| {
-        b.iter(|| aoc22::day09::num_tail_positions_p1(&input))
-    });
-    c.bench_function("day09::num_tail_positions_p2", |b| {
-        b.iter(|| aoc22::day09::num_tail_positions_p2(&input))
-    });
-}
-
-pub fn bench_day10(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day10.txt").unwrap();
-    c.bench_function("day10::sum_of_signal_strengths", |b| {
-        b.iter(|| aoc22::day10::sum_of_signal_strengths(&input))
-    });
-    c.bench_function("day10::render_image", |b| {
-        b.iter(|| aoc22::day10::render_image(&input))
-    });
-}
-
-pub fn bench_day11(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day11.txt").unwrap();
-    c.bench_function("day11::monkey_business_level_p1", |b| {
-        b.iter(|| aoc22::day11::monkey_business_level_p1(&input))
-    });
-    c.bench_function("day11::monkey_business_level_p2", |b| {
-        b.iter(|| aoc22::day11::monkey_business_level_p2(&input))
-    });
-}
-
-pub fn bench_day12(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day12.txt").unwrap();
-    c.bench_function("day12::num_steps_to_target_p1", |b| {
-        b.iter(|| aoc22::day12::num_steps_to_target_p1(&input))
-    });
-    c.bench_function("day12::num_steps_to_target_p2", |b| {
-        b.iter(|| aoc22::day12::num_steps_to_target_p2(&input))
-    });
-}
-
-pub fn bench_day13(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day13.txt").unwrap();
-    c.bench_function("day13::sum_right_indices", |b| {
-        b.iter(|| aoc22::day13::sum_right_indices(&input))
-    });
-    c.bench_function("day13::decoder_key", |b| {
-        b.iter(|| aoc22::day13::decoder_key(&input))
-    });
-}
-
-pub fn bench_day14(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day14.txt").unwrap();
-    c.bench_function("day14::num_resting_sand_units_p1", |b| {
-        b.iter(|| aoc22::day14::num_resting_sand_units_p1(&input))
-    });
-    c.bench_function("day14::num_resting_sand_units_p2", |b| {
-        b.iter(|| aoc22::day14::num_resting_sand_units_p2(&input))
-    });
-}
-
-pub fn bench_day15(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day15.txt").unwrap();
-    c.bench_function("day15::num_empty_positions", |b| {
-        b.iter(|| aoc22::day15::num_empty_positions(&input, 2000000))
-    });
-    c.bench_function("day15::distress_beacon_tuning_frequency", |b| {
-        b.iter(|| aoc22::day15::distress_beacon_tuning_frequency(&input, 4000000))
-    });
-}
-
-pub fn bench_day18(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day18.txt").unwrap();
-    c.bench_function("day18::surface_area", |b| {
-        b.iter(|| aoc22::day18::surface_area(&input))
-    });
-    c.bench_function("day18::external_surface_area", |b| {
-        b.iter(|| aoc22::day18::external_surface_area(&input))
-    });
-}
-
-pub fn bench_day20(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day20.txt").unwrap();
-    c.bench_function("day20::sum_grove_coordinates_p1", |b| {
-        b.iter(|| aoc22::day20::sum_grove_coordinates_p1(&input))
-    });
-    c.bench_function("day20::sum_grove_coordinates_p2", |b| {
-        b.iter(|| aoc22::day20::sum_grove_coordinates_p2(&input))
-    });
-}
-
-pub fn bench_day21(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day21.txt").unwrap();
-    c.bench_function("day21::root_yells", |b| {
-        b.iter(|| aoc22::day21::root_yells(&input))
-    });
-    c.bench_function("day21::i_yell", |b| b.iter(|| aoc22::day21::i_yell(&input)));
-}
-
-pub fn bench_day23(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day23.txt").unwrap();
-    c.bench_function("day23::empty_ground_tiles", |b| {
-        b.iter(|| aoc22::day23::empty_ground_tiles(&input))
-    });
-    c.bench_function("day23::first_idle_round", |b| {
-        b.iter(|| aoc22::day23::first_idle_round(&input))
-    });
-}
-
-pub fn bench_day24(c: &mut Criterion) {
-    let input = fs::read_to_string("input/day24.txt").unwrap();
-    c.bench_function("day24::fewest_minutes_to_goal_p1", |b| {
-        b.iter(|| aoc22::day24::fewest_minutes_to_goal_p1(&input))
-    });
-    c.bench_function("day24::fewest_minutes_to_goal_p2", |b| {
-        b.iter(|| aoc22::day24::fewest_minutes_to_goal_p2(&input))
-    });
-}
-
-criterion_group!(
-    benches,
-    bench_day01,
-    bench_day02,
-    bench_day03,
-    bench_day04,
-    bench_day05,
-    bench_day06,
-    bench_day07,
-    bench_day08,
-    bench_day09,
-    bench_day10,
-    bench_day11,
-    bench_day12,
-    bench_day13,
-    bench_day14,
-    bench_day15,
-    bench_day18,
-    bench_day20,
-    bench_day21,
-    bench_day23,
-    bench_day24,
-);
+use aoc22::alloc;
+use aoc22::inputs::{Inputs, Kind};
+use aoc22::solvers::{self, Context, SOLVERS};
+use criterion::{criterion_group, criterion_main, Criterion, SamplingMode};
+use std::time::Duration;
+
+// Criterion only measures time, so with the `alloc-stats` feature, report what
+// one more run allocates.
+fn report_memory<T>(day: u8, name: &str, f: impl FnOnce() -> T) {
+    if let (_, Some(memory)) = alloc::measure(f) {
+        eprintln!("day{:02}/{}: {}", day, name, memory);
+    }
+}
+
+pub fn bench_solvers(c: &mut Criterion) {
+    let inputs = Inputs::from_env();
+    for day in solvers::days() {
+        let input = match inputs.load(day, Kind::Puzzle) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("skipping day {}: {}", day, e);
+                continue;
+            }
+        };
+
+        // Both parts of a day parse the same way, so parsing is benchmarked
+        // once per day and the parts on the parsed input.
+        let mut solvers = SOLVERS.iter().filter(|s| s.day == day).peekable();
+        let parse = solvers.peek().unwrap().parse;
+        let parsed = match parse(&input) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("skipping day {}: {}", day, e);
+                continue;
+            }
+        };
+
+        // Some solvers take over a minute per run, so use the fewest samples
+        // criterion allows and sample flat to keep a full run bounded.
+        let mut group = c.benchmark_group(format!("day{:02}", day));
+        group
+            .sample_size(10)
+            .sampling_mode(SamplingMode::Flat)
+            .warm_up_time(Duration::from_secs(1));
+        group.bench_function("parse", |b| b.iter(|| parse(&input)));
+        report_memory(day, "parse", || parse(&input));
+        for solver in solvers {
+            let context = Context::new(solver.default_params());
+            group.bench_function(solver.name, |b| {
+                b.iter(|| (solver.solve)(&parsed, &context))
+            });
+            report_memory(day, solver.name, || (solver.solve)(&parsed, &context));
+        }
+        group.finish();
+    }
+}
+
+// Benchmark the sequential and parallel variants of the same parts side by side.
+#[cfg(feature = "parallel")]
+pub fn bench_parallel(c: &mut Criterion) {
+    use aoc22::{day08, day12, day15};
+
+    let inputs = Inputs::from_env();
+    let load = |day| {
+        inputs
+            .load(day, Kind::Puzzle)
+            .map_err(|e| eprintln!("skipping day {}: {}", day, e))
+            .ok()
+    };
+    let mut group = c.benchmark_group("parallel");
+    group
+        .sample_size(10)
+        .sampling_mode(SamplingMode::Flat)
+        .warm_up_time(Duration::from_secs(1));
+    if let Some(Ok(trees)) = load(8).map(|input| day08::parse(&input)) {
+        group.bench_function("day08a/sequential", |b| {
+            b.iter(|| day08::part1_sequential(&trees))
+        });
+        group.bench_function("day08a/parallel", |b| {
+            b.iter(|| day08::part1_parallel(&trees))
+        });
+        group.bench_function("day08b/sequential", |b| {
+            b.iter(|| day08::part2_sequential(&trees))
+        });
+        group.bench_function("day08b/parallel", |b| {
+            b.iter(|| day08::part2_parallel(&trees))
+        });
+    }
+    if let Some(Ok(map)) = load(12).map(|input| day12::parse(&input)) {
+        group.bench_function("day12b/sequential", |b| {
+            b.iter(|| day12::part2_sequential(&map))
+        });
+        group.bench_function("day12b/parallel", |b| {
+            b.iter(|| day12::part2_parallel(&map))
+        });
+    }
+    if let Some(Ok(report)) = load(15).map(|input| day15::parse(&input)) {
+        group.bench_function("day15b/sequential", |b| {
+            b.iter(|| day15::distress_beacon_tuning_frequency_sequential(&report, day15::BOUND))
+        });
+        group.bench_function("day15b/parallel", |b| {
+            b.iter(|| day15::distress_beacon_tuning_frequency_parallel(&report, day15::BOUND))
+        });
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "parallel"))]
+criterion_group!(benches, bench_solvers);
+#[cfg(feature = "parallel")]
+criterion_group!(benches, bench_solvers, bench_parallel);
 criterion_main!(benches);
@@ -0,0 +1,170 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+
+/// Memory allocated by a thread while running some code.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Stats {
+    /// Most bytes allocated at once, beyond those allocated before.
+    pub peak_bytes: usize,
+    /// Bytes allocated in total, including those freed since.
+    pub total_bytes: usize,
+    pub allocations: usize,
+}
+
+// Format a number of bytes with a binary unit, e.g. `1.5 MiB`.
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "peak: {}, total: {} in {} allocations",
+            format_bytes(self.peak_bytes),
+            format_bytes(self.total_bytes),
+            self.allocations
+        )
+    }
+}
+
+// Counters of the current thread, so that solvers running side by side do not
+// count each other's allocations. Memory freed by another thread than the one
+// allocating it is not counted as freed.
+#[derive(Clone, Copy)]
+struct Counters {
+    current: usize,
+    peak: usize,
+    total: usize,
+    allocations: usize,
+}
+
+thread_local! {
+    static COUNTERS: Cell<Counters> = const {
+        Cell::new(Counters {
+            current: 0,
+            peak: 0,
+            total: 0,
+            allocations: 0,
+        })
+    };
+}
+
+// Update the counters of the current thread, unless they are already gone
+// because the thread is exiting.
+fn update(f: impl FnOnce(&mut Counters)) {
+    let _ = COUNTERS.try_with(|counters| {
+        let mut c = counters.get();
+        f(&mut c);
+        counters.set(c);
+    });
+}
+
+/// Wraps the system allocator to count the allocations of each thread. It is
+/// the global allocator when the `alloc-stats` feature is enabled.
+pub struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            update(|c| {
+                c.current += layout.size();
+                c.peak = c.peak.max(c.current);
+                c.total += layout.size();
+                c.allocations += 1;
+            });
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        update(|c| c.current = c.current.saturating_sub(layout.size()));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            update(|c| {
+                c.current = c.current.saturating_sub(layout.size()) + new_size;
+                c.peak = c.peak.max(c.current);
+                c.total += new_size;
+                c.allocations += 1;
+            });
+        }
+        new_ptr
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Return whether allocations are counted, i.e. whether the `alloc-stats`
+/// feature is enabled. They are not with the `parallel` feature, since
+/// counters of the current thread miss what solvers allocate on rayon's
+/// threads.
+pub fn enabled() -> bool {
+    cfg!(all(feature = "alloc-stats", not(feature = "parallel")))
+}
+
+/// Run `f` and return what the current thread allocated meanwhile, or `None`
+/// when allocations are not counted.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<Stats>) {
+    let mut before = None;
+    update(|c| {
+        before = Some(*c);
+        c.peak = c.current;
+    });
+    let result = f();
+    let mut after = None;
+    update(|c| after = Some(*c));
+    let stats = match (before, after) {
+        (Some(before), Some(after)) if enabled() => Some(Stats {
+            peak_bytes: after.peak - before.current,
+            total_bytes: after.total - before.total,
+            allocations: after.allocations - before.allocations,
+        }),
+        _ => None,
+    };
+    // Keep the peak of the whole thread for enclosing measurements.
+    if let Some(before) = before {
+        update(|c| c.peak = c.peak.max(before.peak));
+    }
+    (result, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let (v, stats) = measure(|| vec![0u8; 4096].len());
+        assert_eq!(v, 4096);
+        if enabled() {
+            let stats = stats.unwrap();
+            assert!(stats.peak_bytes >= 4096);
+            assert!(stats.total_bytes >= 4096);
+            assert!(stats.allocations >= 1);
+        } else {
+            assert_eq!(stats, None);
+        }
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 << 20), "3.0 MiB");
+    }
+}
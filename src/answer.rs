@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// The answer to a part of a puzzle.
+#[derive(Debug, Clone)]
+pub enum Answer {
+    Signed(i64),
+    Unsigned(u64),
+    /// An integer too large for the other integer variants.
+    Big(i128),
+    Text(String),
+    /// Rows of characters to be read by eye, such as letters drawn on a CRT.
+    Image(Vec<String>),
+}
+
+impl Answer {
+    /// Return the value of an integer answer.
+    pub fn as_integer(&self) -> Option<i128> {
+        match *self {
+            Answer::Signed(n) => Some(n.into()),
+            Answer::Unsigned(n) => Some(n.into()),
+            Answer::Big(n) => Some(n),
+            Answer::Text(_) | Answer::Image(_) => None,
+        }
+    }
+
+    /// Return whether the answer spans several lines.
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Answer::Image(rows) if rows.len() > 1)
+    }
+}
+
+// Return the rows of an image without trailing whitespace or blank rows.
+fn trimmed(rows: &[String]) -> Vec<&str> {
+    let mut rows: Vec<&str> = rows.iter().map(|row| row.trim_end()).collect();
+    while rows.last() == Some(&"") {
+        rows.pop();
+    }
+    rows
+}
+
+/// Integers are equal whatever their variant, and images are equal when they
+/// only differ by trailing whitespace.
+impl PartialEq for Answer {
+    fn eq(&self, other: &Answer) -> bool {
+        match (self, other) {
+            (Answer::Text(a), Answer::Text(b)) => a == b,
+            (Answer::Image(a), Answer::Image(b)) => trimmed(a) == trimmed(b),
+            _ => self.as_integer().is_some() && self.as_integer() == other.as_integer(),
+        }
+    }
+}
+
+impl Eq for Answer {}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Signed(n) => write!(f, "{}", n),
+            Answer::Unsigned(n) => write!(f, "{}", n),
+            Answer::Big(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Image(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Answer {
+        Answer::Signed(n)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(n: u64) -> Answer {
+        Answer::Unsigned(n)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Answer {
+        Answer::Unsigned(n as u64)
+    }
+}
+
+impl From<i128> for Answer {
+    fn from(n: i128) -> Answer {
+        Answer::Big(n)
+    }
+}
+
+/// Text spanning several lines becomes an image.
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        if s.contains('\n') {
+            Answer::Image(s.lines().map(String::from).collect())
+        } else {
+            Answer::Text(s)
+        }
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Answer {
+        Answer::from(s.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Answer::from(42usize), Answer::from(42i64));
+        assert_eq!(Answer::Big(-7), Answer::Signed(-7));
+        assert_ne!(Answer::from(42i64), Answer::from("42"));
+        assert_eq!(Answer::from(u64::MAX).to_string(), "18446744073709551615");
+
+        let image = Answer::from("#..#\n.##.");
+        assert!(image.is_multiline());
+        assert_eq!(image.to_string(), "#..#\n.##.");
+        assert_eq!(image, Answer::from("#..#  \n.##.\n\n"));
+        assert_ne!(image, Answer::from("#..#\n.#.."));
+        assert_eq!(Answer::from("CMZ"), Answer::Text(String::from("CMZ")));
+    }
+}
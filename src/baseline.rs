@@ -0,0 +1,151 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Mean run time in nanoseconds per benchmark, keyed by `<group>/<function>`.
+pub type Estimates = BTreeMap<String, f64>;
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Read the latest mean estimates criterion saved under the given directory,
+/// normally `target/criterion`.
+pub fn read_criterion_estimates(dir: &Path) -> io::Result<Estimates> {
+    let mut estimates = Estimates::new();
+    for group in fs::read_dir(dir)? {
+        let group = group?;
+        if !group.file_type()?.is_dir() || group.file_name() == "report" {
+            continue;
+        }
+        for function in fs::read_dir(group.path())? {
+            let function = function?;
+            let path = function.path().join("new").join("estimates.json");
+            if !path.is_file() {
+                continue;
+            }
+            let value: Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            let mean = value["mean"]["point_estimate"]
+                .as_f64()
+                .ok_or_else(|| invalid_data(format!("{}: no mean estimate", path.display())))?;
+            estimates.insert(
+                format!(
+                    "{}/{}",
+                    group.file_name().to_string_lossy(),
+                    function.file_name().to_string_lossy()
+                ),
+                mean,
+            );
+        }
+    }
+    Ok(estimates)
+}
+
+/// Load estimates stored with [`save`].
+pub fn load(path: &Path) -> io::Result<Estimates> {
+    let value: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| invalid_data(format!("{}: expected an object", path.display())))?;
+    object
+        .iter()
+        .map(|(id, mean)| {
+            mean.as_f64()
+                .map(|mean| (id.clone(), mean))
+                .ok_or_else(|| invalid_data(format!("{}: bad estimate for {}", path.display(), id)))
+        })
+        .collect()
+}
+
+/// Store estimates as a JSON object of mean nanoseconds per benchmark.
+pub fn save(path: &Path, estimates: &Estimates) -> io::Result<()> {
+    let object: Map<String, Value> = estimates
+        .iter()
+        .map(|(id, &mean)| (id.clone(), Value::from(mean.round())))
+        .collect();
+    fs::write(path, serde_json::to_string_pretty(&object)? + "\n")
+}
+
+/// A benchmark present in both the baseline and the current run.
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub id: String,
+    pub baseline: f64,
+    pub current: f64,
+}
+
+impl Change {
+    /// Return the change in run time relative to the baseline, in percent.
+    pub fn percent(&self) -> f64 {
+        (self.current - self.baseline) / self.baseline * 100.0
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Comparison {
+    pub changes: Vec<Change>,
+    /// Benchmarks in the current run that have no baseline.
+    pub new: Vec<String>,
+    /// Baseline benchmarks missing from the current run.
+    pub missing: Vec<String>,
+}
+
+impl Comparison {
+    /// Return the changes slower than the baseline by more than the given
+    /// percentage.
+    pub fn regressions(&self, threshold_percent: f64) -> Vec<&Change> {
+        self.changes
+            .iter()
+            .filter(|c| c.percent() > threshold_percent)
+            .collect()
+    }
+}
+
+pub fn compare(baseline: &Estimates, current: &Estimates) -> Comparison {
+    let mut comparison = Comparison::default();
+    for (id, &current) in current {
+        match baseline.get(id) {
+            Some(&baseline) => comparison.changes.push(Change {
+                id: id.clone(),
+                baseline,
+                current,
+            }),
+            None => comparison.new.push(id.clone()),
+        }
+    }
+    comparison.missing = baseline
+        .keys()
+        .filter(|id| !current.contains_key(*id))
+        .cloned()
+        .collect();
+    comparison
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let baseline: Estimates = [("day01/a", 100.0), ("day01/b", 200.0), ("day02/a", 50.0)]
+            .iter()
+            .map(|&(id, mean)| (id.to_string(), mean))
+            .collect();
+        let current: Estimates = [("day01/a", 104.0), ("day01/b", 300.0), ("day16/a", 1e9)]
+            .iter()
+            .map(|&(id, mean)| (id.to_string(), mean))
+            .collect();
+        let comparison = compare(&baseline, &current);
+        assert_eq!(comparison.new, vec!["day16/a"]);
+        assert_eq!(comparison.missing, vec!["day02/a"]);
+        let regressions: Vec<&str> = comparison
+            .regressions(5.0)
+            .iter()
+            .map(|c| c.id.as_str())
+            .collect();
+        assert_eq!(regressions, vec!["day01/b"]);
+        assert!(comparison.regressions(50.0).is_empty());
+    }
+}
@@ -0,0 +1,282 @@
+use crate::inputs::{Inputs, Kind};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Environment variable holding the session token.
+pub const SESSION_VAR: &str = "AOC22_SESSION";
+
+/// Environment variable naming a file that holds the session token.
+pub const SESSION_FILE_VAR: &str = "AOC22_SESSION_FILE";
+
+/// File the session token is read from when neither variable is set.
+pub const DEFAULT_SESSION_FILE: &str = ".session";
+
+/// Environment variable overriding [`DEFAULT_BASE_URL`].
+pub const BASE_URL_VAR: &str = "AOC22_BASE_URL";
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com/2022";
+
+/// Least time between two requests made by the same client.
+pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(3);
+
+const USER_AGENT: &str = concat!("aoc22/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// No session token in the environment or the session file.
+    NoSession(PathBuf),
+    /// The server answered with an error status.
+    Status(u16, String),
+    Transport(String),
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoSession(path) => write!(
+                f,
+                "no session token: set {} or write it to {}",
+                SESSION_VAR,
+                path.display()
+            ),
+            Self::Status(code, url) => write!(f, "{} returned status {}", url, code),
+            Self::Transport(e) => write!(f, "request failed: {}", e),
+            Self::Io(path, e) => write!(f, "failed to access {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// Read the session token from [`SESSION_VAR`], or else from the file named by
+/// [`SESSION_FILE_VAR`], falling back to [`DEFAULT_SESSION_FILE`].
+pub fn session_from_env() -> Result<String, ClientError> {
+    if let Ok(session) = env::var(SESSION_VAR) {
+        return Ok(session.trim().to_string());
+    }
+    let path =
+        PathBuf::from(env::var_os(SESSION_FILE_VAR).unwrap_or_else(|| DEFAULT_SESSION_FILE.into()));
+    match fs::read_to_string(&path) {
+        Ok(session) if !session.trim().is_empty() => Ok(session.trim().to_string()),
+        Ok(_) => Err(ClientError::NoSession(path)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(ClientError::NoSession(path)),
+        Err(e) => Err(ClientError::Io(path, e)),
+    }
+}
+
+/// A rate-limited HTTP client for the puzzle website.
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    min_interval: Duration,
+    last_request: Option<Instant>,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str) -> Client {
+        Client {
+            agent: ureq::AgentBuilder::new()
+                .user_agent(USER_AGENT)
+                .timeout(Duration::from_secs(30))
+                .build(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            min_interval: DEFAULT_MIN_INTERVAL,
+            last_request: None,
+        }
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Client {
+        self.min_interval = min_interval;
+        self
+    }
+
+    // Sleep until at least the minimum interval has passed since the last
+    // request.
+    fn wait_turn(&mut self) {
+        if let Some(last) = self.last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_interval {
+                thread::sleep(self.min_interval - elapsed);
+            }
+        }
+        self.last_request = Some(Instant::now());
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    fn read_response(
+        url: String,
+        result: Result<ureq::Response, ureq::Error>,
+    ) -> Result<String, ClientError> {
+        match result {
+            Ok(response) => response
+                .into_string()
+                .map_err(|e| ClientError::Transport(e.to_string())),
+            Err(ureq::Error::Status(code, _)) => Err(ClientError::Status(code, url)),
+            Err(e) => Err(ClientError::Transport(e.to_string())),
+        }
+    }
+
+    /// Return the body of the page at the given path below the base URL.
+    pub fn get(&mut self, path: &str) -> Result<String, ClientError> {
+        self.wait_turn();
+        let url = self.url(path);
+        let result = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call();
+        Client::read_response(url, result)
+    }
+
+    /// Post the given form fields to the path below the base URL and return
+    /// the body of the response.
+    pub fn post_form(
+        &mut self,
+        path: &str,
+        fields: &[(&str, &str)],
+    ) -> Result<String, ClientError> {
+        self.wait_turn();
+        let url = self.url(path);
+        let result = self
+            .agent
+            .post(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .send_form(fields);
+        Client::read_response(url, result)
+    }
+
+    /// Download the puzzle input of the given day into the input directory,
+    /// unless it is already there, and return its path.
+    pub fn fetch_input(&mut self, inputs: &Inputs, day: u8) -> Result<PathBuf, ClientError> {
+        let path = inputs.path(day, Kind::Puzzle);
+        if path.exists() {
+            return Ok(path);
+        }
+        let input = self.get(&format!("day/{}/input", day))?;
+        let io_error = |e| ClientError::Io(path.clone(), e);
+        fs::create_dir_all(inputs.dir()).map_err(io_error)?;
+        // Write to a temporary file first so an interrupted download never
+        // leaves a partial input behind.
+        let partial = path.with_extension("part");
+        fs::write(&partial, input).map_err(io_error)?;
+        fs::rename(&partial, &path).map_err(io_error)?;
+        Ok(path)
+    }
+}
+
+/// A local HTTP server answering every request with a fixed body, recording
+/// the requests it received.
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    pub struct MockServer {
+        pub url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockServer {
+        pub fn start(status: u16, body: &'static str) -> MockServer {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/2022", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&requests);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = stream.unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request = String::new();
+                    let mut content_length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if let Some(len) = line.to_lowercase().strip_prefix("content-length:") {
+                            content_length = len.trim().parse().unwrap();
+                        }
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                        request.push_str(&line);
+                    }
+                    let mut content = vec![0; content_length];
+                    std::io::Read::read_exact(&mut reader, &mut content).unwrap();
+                    request.push_str(&String::from_utf8(content).unwrap());
+                    recorded.lock().unwrap().push(request);
+                    write!(
+                        stream,
+                        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                }
+            });
+            MockServer { url, requests }
+        }
+
+        pub fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::MockServer;
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc22-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_fetch_input() {
+        let server = MockServer::start(200, "1000\n2000\n");
+        let inputs = Inputs::new(temp_dir("fetch"));
+        let mut client =
+            Client::new(&server.url, "abc123").with_min_interval(Duration::from_millis(200));
+
+        let start = Instant::now();
+        let path = client.fetch_input(&inputs, 1).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1000\n2000\n");
+        assert_eq!(client.fetch_input(&inputs, 1).unwrap(), path);
+        client.fetch_input(&inputs, 12).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("GET /2022/day/1/input "));
+        assert!(requests[0].contains("session=abc123"));
+        assert!(requests[1].starts_with("GET /2022/day/12/input "));
+        fs::remove_dir_all(inputs.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_input_status() {
+        let server = MockServer::start(404, "Not found");
+        let inputs = Inputs::new(temp_dir("fetch-status"));
+        let mut client = Client::new(&server.url, "abc123");
+        assert!(matches!(
+            client.fetch_input(&inputs, 25),
+            Err(ClientError::Status(404, _))
+        ));
+        assert!(!inputs.path(25, Kind::Puzzle).exists());
+    }
+}
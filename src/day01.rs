@@ -1,41 +1,86 @@
-use std::collections::BinaryHeap;
+use std::cmp::Reverse;
+use std::str::FromStr;
 
-/// Find the maximum total calories carried by any elf.
-pub fn max_total_calories(s: &str) -> i64 {
-    s.trim()
-        .split("\n\n")
-        .map(|elf| {
-            elf.trim()
-                .split('\n')
-                .filter_map(|item| item.trim().parse::<i64>().ok())
-                .sum()
-        })
-        .max()
-        .unwrap_or(0)
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseInventoryError {
+    /// One-based line number of the offending line.
+    pub line: usize,
+}
+
+/// Calories carried by each elf, in input order.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Inventory {
+    totals: Vec<i64>,
+}
+
+impl FromStr for Inventory {
+    type Err = ParseInventoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut totals = Vec::new();
+        let mut current: Option<i64> = None;
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                totals.extend(current.take());
+                continue;
+            }
+            let calories: i64 = line
+                .parse()
+                .map_err(|_| ParseInventoryError { line: i + 1 })?;
+            current = Some(current.unwrap_or(0) + calories);
+        }
+        totals.extend(current);
+        Ok(Inventory { totals })
+    }
+}
+
+impl Inventory {
+    /// Return the total calories carried by each elf.
+    pub fn totals(&self) -> &[i64] {
+        &self.totals
+    }
+
+    /// Return the (index, total) of the `k` elves carrying the most calories,
+    /// most first. Ties are broken by lower index.
+    pub fn top_k(&self, k: usize) -> Vec<(usize, i64)> {
+        let mut elves: Vec<(usize, i64)> = self.totals.iter().copied().enumerate().collect();
+        let key = |&(i, total): &(usize, i64)| (Reverse(total), i);
+        let k = k.min(elves.len());
+        if k < elves.len() && k > 0 {
+            elves.select_nth_unstable_by_key(k - 1, key);
+        }
+        elves.truncate(k);
+        elves.sort_unstable_by_key(key);
+        elves
+    }
+
+    /// Return the total calories carried by the `k` elves carrying the most.
+    pub fn top_k_total(&self, k: usize) -> i64 {
+        self.top_k(k).iter().map(|(_, total)| total).sum()
+    }
+}
+
+/// Parse the calories listed in the puzzle input.
+pub fn parse(s: &str) -> Result<Inventory, ParseInventoryError> {
+    s.parse()
 }
 
 /// Find the maximum total calories carried by any elf.
-pub fn max3_total_calories(s: &str) -> i64 {
-    let mut cals: BinaryHeap<i64> = s
-        .trim()
-        .split("\n\n")
-        .map(|elf| {
-            elf.trim()
-                .split('\n')
-                .filter_map(|item| item.trim().parse::<i64>().ok())
-                .sum()
-        })
-        .collect();
-    (0..3).filter_map(|_| cals.pop()).sum()
+pub fn part1(inventory: &Inventory) -> i64 {
+    inventory.top_k_total(1)
+}
+
+/// Find the total calories carried by the top three elves.
+pub fn part2(inventory: &Inventory) -> i64 {
+    inventory.top_k_total(3)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_max_total_calories() {
-        let s = "1000
+    static INPUT: &str = "1000
 2000
 3000
 
@@ -49,25 +94,27 @@ mod tests {
 9000
 
 10000";
-        assert_eq!(max_total_calories(s), 24000);
-    }
 
     #[test]
-    fn test_max3_total_calories() {
-        let s = "1000
-2000
-3000
-
-4000
-
-5000
-6000
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 24000);
+    }
 
-7000
-8000
-9000
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 45000);
+    }
 
-10000";
-        assert_eq!(max3_total_calories(s), 45000);
+    #[test]
+    fn test_inventory() {
+        let inventory = parse(INPUT).unwrap();
+        assert_eq!(inventory.totals(), &[6000, 4000, 11000, 24000, 10000]);
+        assert_eq!(inventory.top_k(2), vec![(3, 24000), (2, 11000)]);
+        assert_eq!(inventory.top_k(0), vec![]);
+        assert_eq!(inventory.top_k(10).len(), 5);
+        assert_eq!(
+            "1000\n\nabc\n".parse::<Inventory>(),
+            Err(ParseInventoryError { line: 3 })
+        );
     }
 }
@@ -1,33 +1,15 @@
-use std::str::FromStr;
-
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
-enum Hand {
+pub enum Hand {
     Rock,
     Paper,
     Scissors,
 }
 
-impl FromStr for Hand {
-    type Err = ();
-
-    fn from_str(input: &str) -> Result<Hand, Self::Err> {
-        match input {
-            "A" => Ok(Hand::Rock),
-            "B" => Ok(Hand::Paper),
-            "C" => Ok(Hand::Scissors),
-
-            "X" => Ok(Hand::Rock),
-            "Y" => Ok(Hand::Paper),
-            "Z" => Ok(Hand::Scissors),
-
-            _ => Err(()),
-        }
-    }
-}
-
 impl Hand {
+    pub const ALL: [Hand; 3] = [Hand::Rock, Hand::Paper, Hand::Scissors];
+
     // Return the bonus score for this hand.
-    fn bonus(&self) -> i64 {
+    pub fn bonus(&self) -> i64 {
         match &self {
             Hand::Rock => 1,
             Hand::Paper => 2,
@@ -36,36 +18,24 @@ impl Hand {
     }
 
     // Return the score for this Hand played against the given Hand.
-    fn score_against(self, h: Hand) -> i64 {
+    pub fn score_against(self, h: Hand) -> i64 {
         let outcome = Outcome::from(h, self);
         outcome.score() + self.bonus()
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-enum Outcome {
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Outcome {
     Win,
     Loss,
     Draw,
 }
 
-impl FromStr for Outcome {
-    type Err = ();
-
-    fn from_str(input: &str) -> Result<Outcome, Self::Err> {
-        match input {
-            "X" => Ok(Outcome::Loss),
-            "Y" => Ok(Outcome::Draw),
-            "Z" => Ok(Outcome::Win),
-
-            _ => Err(()),
-        }
-    }
-}
-
 impl Outcome {
+    pub const ALL: [Outcome; 3] = [Outcome::Loss, Outcome::Draw, Outcome::Win];
+
     // Create the Outcome of two Hands.
-    fn from(h1: Hand, h2: Hand) -> Outcome {
+    pub fn from(h1: Hand, h2: Hand) -> Outcome {
         match (h1, h2) {
             (Hand::Scissors, Hand::Rock)
             | (Hand::Paper, Hand::Scissors)
@@ -76,7 +46,7 @@ impl Outcome {
     }
 
     // Return the score for this outcome.
-    fn score(&self) -> i64 {
+    pub fn score(&self) -> i64 {
         match &self {
             Outcome::Win => 6,
             Outcome::Draw => 3,
@@ -85,7 +55,7 @@ impl Outcome {
     }
 
     // Return a response for given Hand that will produce this outcome.
-    fn response(&self, h: Hand) -> Hand {
+    pub fn response(&self, h: Hand) -> Hand {
         match (h, &self) {
             (x, Outcome::Draw) => x,
             (Hand::Rock, Outcome::Win) => Hand::Paper,
@@ -98,40 +68,231 @@ impl Outcome {
     }
 
     // Return the score when played against the given Hand for this outcome.
-    fn score_against(&self, h: Hand) -> i64 {
+    pub fn score_against(&self, h: Hand) -> i64 {
         let other = &self.response(h);
         self.score() + other.bonus()
     }
 }
 
-// Parse the line and return a tuple of Hands.
-fn parse_line_p1(s: &str) -> (Hand, Hand) {
-    let v: Vec<&str> = s.split_whitespace().collect();
-    (v[0].parse().unwrap(), v[1].parse().unwrap())
+/// The meaning of the three symbols in the second column of the guide.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Interpretation {
+    Hands([Hand; 3]),
+    Outcomes([Outcome; 3]),
 }
 
-/// Find the total score.
-pub fn total_score_p1(s: &str) -> i64 {
+impl Interpretation {
+    /// Return every interpretation: each permutation of hands and each
+    /// permutation of outcomes.
+    pub fn all() -> Vec<Interpretation> {
+        let mut v: Vec<Interpretation> = permutations(Hand::ALL)
+            .into_iter()
+            .map(Interpretation::Hands)
+            .collect();
+        v.extend(
+            permutations(Outcome::ALL)
+                .into_iter()
+                .map(Interpretation::Outcomes),
+        );
+        v
+    }
+
+    // Return the response for the given symbol index against the given Hand.
+    fn response(&self, i: usize, opponent: Hand) -> Hand {
+        match self {
+            Interpretation::Hands(hands) => hands[i],
+            Interpretation::Outcomes(outcomes) => outcomes[i].response(opponent),
+        }
+    }
+}
+
+// Return all six orderings of the given three items.
+fn permutations<T: Copy>([a, b, c]: [T; 3]) -> Vec<[T; 3]> {
+    vec![
+        [a, b, c],
+        [a, c, b],
+        [b, a, c],
+        [b, c, a],
+        [c, a, b],
+        [c, b, a],
+    ]
+}
+
+/// Symbol mapping for both columns of the strategy guide.
+#[derive(Debug, Clone)]
+pub struct Mapping {
+    /// Symbols for the opponent's Rock, Paper and Scissors.
+    pub opponent: [char; 3],
+    /// Symbols for the second column, in the order used by `interpretation`.
+    pub response: [char; 3],
+    pub interpretation: Interpretation,
+}
+
+impl Mapping {
+    /// The mapping from part 1: X, Y and Z are the hands to play.
+    pub fn part1() -> Mapping {
+        Mapping {
+            opponent: ['A', 'B', 'C'],
+            response: ['X', 'Y', 'Z'],
+            interpretation: Interpretation::Hands(Hand::ALL),
+        }
+    }
+
+    /// The mapping from part 2: X, Y and Z are the outcomes to reach.
+    pub fn part2() -> Mapping {
+        Mapping {
+            interpretation: Interpretation::Outcomes(Outcome::ALL),
+            ..Mapping::part1()
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseRoundError;
+
+/// A line of the guide with each column resolved to its symbol index.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+struct Symbols(usize, usize);
+
+impl Symbols {
+    fn parse(s: &str, mapping: &Mapping) -> Result<Symbols, ParseRoundError> {
+        let index = |token: &str, symbols: &[char; 3]| {
+            let mut chars = token.chars();
+            match (chars.next(), chars.next()) {
+                (Some(ch), None) => symbols.iter().position(|&s| s == ch),
+                _ => None,
+            }
+            .ok_or(ParseRoundError)
+        };
+        let v: Vec<&str> = s.split_whitespace().collect();
+        if v.len() != 2 {
+            return Err(ParseRoundError);
+        }
+        Ok(Symbols(
+            index(v[0], &mapping.opponent)?,
+            index(v[1], &mapping.response)?,
+        ))
+    }
+}
+
+fn parse_guide(s: &str, mapping: &Mapping) -> Result<Vec<Symbols>, ParseRoundError> {
     s.trim()
-        .split('\n')
-        .map(parse_line_p1)
-        .map(|(h1, h2)| h2.score_against(h1))
+        .lines()
+        .map(|line| Symbols::parse(line, mapping))
+        .collect()
+}
+
+/// A single round of the tournament.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Round {
+    pub opponent: Hand,
+    pub response: Hand,
+}
+
+/// The score of a single round, broken down by its parts.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct RoundScore {
+    pub outcome: Outcome,
+    pub shape_score: i64,
+    pub outcome_score: i64,
+}
+
+impl RoundScore {
+    pub fn total(&self) -> i64 {
+        self.shape_score + self.outcome_score
+    }
+}
+
+impl Round {
+    pub fn score(&self) -> RoundScore {
+        let outcome = Outcome::from(self.opponent, self.response);
+        RoundScore {
+            outcome,
+            shape_score: self.response.bonus(),
+            outcome_score: outcome.score(),
+        }
+    }
+}
+
+/// Parse the strategy guide into rounds using the given mapping.
+pub fn parse_rounds(s: &str, mapping: &Mapping) -> Result<Vec<Round>, ParseRoundError> {
+    Ok(parse_guide(s, mapping)?
+        .iter()
+        .map(|&Symbols(o, r)| {
+            let opponent = Hand::ALL[o];
+            Round {
+                opponent,
+                response: mapping.interpretation.response(r, opponent),
+            }
+        })
+        .collect())
+}
+
+/// Whether to look for the highest or the lowest scoring interpretation.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Goal {
+    Maximize,
+    Minimize,
+}
+
+/// Find the interpretation of the second column that gives the highest or
+/// lowest total score, reading the opponent column with the given mapping.
+pub fn best_interpretation(
+    s: &str,
+    mapping: &Mapping,
+    goal: Goal,
+) -> Result<(Interpretation, i64), ParseRoundError> {
+    let guide = parse_guide(s, mapping)?;
+    let totals = Interpretation::all().into_iter().map(|interpretation| {
+        let total = guide
+            .iter()
+            .map(|&Symbols(o, r)| {
+                let opponent = Hand::ALL[o];
+                interpretation.response(r, opponent).score_against(opponent)
+            })
+            .sum::<i64>();
+        (interpretation, total)
+    });
+    let best = match goal {
+        Goal::Maximize => totals.max_by_key(|&(_, total)| total),
+        Goal::Minimize => totals.min_by_key(|&(_, total)| total),
+    };
+    Ok(best.unwrap())
+}
+
+/// The strategy guide with each column resolved to its symbol index, before
+/// the second column is interpreted.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Guide(Vec<Symbols>);
+
+/// Parse the strategy guide. Both parts use the same symbols, they only
+/// interpret the second column differently.
+pub fn parse(s: &str) -> Result<Guide, ParseRoundError> {
+    parse_guide(s, &Mapping::part1()).map(Guide)
+}
+
+fn total_score(guide: &Guide, mapping: &Mapping) -> i64 {
+    guide
+        .0
+        .iter()
+        .map(|&Symbols(o, r)| {
+            let opponent = Hand::ALL[o];
+            mapping
+                .interpretation
+                .response(r, opponent)
+                .score_against(opponent)
+        })
         .sum()
 }
 
-// Parse the line and return a tuple of Hand and Outcome.
-fn parse_line_p2(s: &str) -> (Hand, Outcome) {
-    let v: Vec<&str> = s.split_whitespace().collect();
-    (v[0].parse().unwrap(), v[1].parse().unwrap())
+/// Find the total score.
+pub fn part1(guide: &Guide) -> i64 {
+    total_score(guide, &Mapping::part1())
 }
 
 /// Find the total score.
-pub fn total_score_p2(s: &str) -> i64 {
-    s.trim()
-        .split('\n')
-        .map(parse_line_p2)
-        .map(|(h, o)| o.score_against(h))
-        .sum()
+pub fn part2(guide: &Guide) -> i64 {
+    total_score(guide, &Mapping::part2())
 }
 
 #[cfg(test)]
@@ -139,18 +300,73 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_total_score_p1() {
+    fn test_part1() {
+        let s = "A Y
+B X
+C Z";
+        assert_eq!(part1(&parse(s).unwrap()), 15);
+    }
+
+    #[test]
+    fn test_part2() {
         let s = "A Y
 B X
 C Z";
-        assert_eq!(total_score_p1(s), 15);
+        assert_eq!(part2(&parse(s).unwrap()), 12);
+    }
+
+    #[test]
+    fn test_round_scores() {
+        let mapping = Mapping {
+            opponent: ['R', 'P', 'S'],
+            response: ['l', 'd', 'w'],
+            interpretation: Interpretation::Outcomes(Outcome::ALL),
+        };
+        let scores: Vec<RoundScore> = parse_rounds("R d\nP l\nS w", &mapping)
+            .unwrap()
+            .iter()
+            .map(|r| r.score())
+            .collect();
+        assert_eq!(
+            scores,
+            vec![
+                RoundScore {
+                    outcome: Outcome::Draw,
+                    shape_score: 1,
+                    outcome_score: 3
+                },
+                RoundScore {
+                    outcome: Outcome::Loss,
+                    shape_score: 1,
+                    outcome_score: 0
+                },
+                RoundScore {
+                    outcome: Outcome::Win,
+                    shape_score: 1,
+                    outcome_score: 6
+                },
+            ]
+        );
+        assert_eq!(parse_rounds("A Y", &mapping), Err(ParseRoundError));
     }
 
     #[test]
-    fn test_total_score_p2() {
+    fn test_best_interpretation() {
         let s = "A Y
 B X
 C Z";
-        assert_eq!(total_score_p2(s), 12);
+        let mapping = Mapping::part1();
+        assert_eq!(Interpretation::all().len(), 12);
+        assert_eq!(
+            best_interpretation(s, &mapping, Goal::Maximize).unwrap(),
+            (
+                Interpretation::Hands([Hand::Scissors, Hand::Paper, Hand::Rock]),
+                24
+            )
+        );
+        assert_eq!(
+            best_interpretation(s, &mapping, Goal::Minimize).unwrap().1,
+            6
+        );
     }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::str::FromStr;
 
 fn priority(ch: char) -> i64 {
     let v = (ch as i64) - 96;
@@ -9,58 +9,211 @@ fn priority(ch: char) -> i64 {
     }
 }
 
-fn parse_line(s: &str) -> i64 {
-    let (first, last) = s.split_at(s.len() / 2);
-    let s1 = first.chars().collect::<HashSet<_>>();
-    let s2 = &last.chars().collect::<HashSet<_>>();
-    let mut ixn = s1.intersection(s2);
-    let ch = ixn.next().unwrap();
-    priority(*ch)
+// Return the item for the given priority.
+fn item(priority: u32) -> char {
+    if priority <= 26 {
+        (b'a' + (priority - 1) as u8) as char
+    } else {
+        (b'A' + (priority - 27) as u8) as char
+    }
+}
+
+/// A set of items, stored as a bitmask where bit `p` is set for the item with
+/// priority `p`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    fn from_items(s: &str) -> Option<ItemSet> {
+        s.chars().try_fold(ItemSet(0), |set, ch| {
+            ch.is_ascii_alphabetic()
+                .then(|| ItemSet(set.0 | 1 << priority(ch)))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, ch: char) -> bool {
+        ch.is_ascii_alphabetic() && self.0 & 1 << priority(ch) != 0
+    }
+
+    pub fn intersection(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = char> + '_ {
+        (1..=52).filter(|p| self.0 & 1 << p != 0).map(item)
+    }
+
+    /// Return the sum of priorities of all items in the set.
+    pub fn priority_sum(&self) -> i64 {
+        self.iter().map(priority).sum()
+    }
+
+    // Return the only item in the set.
+    fn single(&self) -> Result<char, CommonItemError> {
+        match self.len() {
+            0 => Err(CommonItemError::NoneShared),
+            1 => Ok(item(self.0.trailing_zeros())),
+            _ => Err(CommonItemError::MultipleShared(*self)),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseRucksackError;
+
+/// Error for a set of item lists expected to share exactly one item.
+#[derive(Debug, Eq, PartialEq)]
+pub enum CommonItemError {
+    NoneShared,
+    MultipleShared(ItemSet),
+    /// The rucksacks cannot be split into groups of the given size.
+    InvalidGroupSize(usize),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Rucksack {
+    first: ItemSet,
+    second: ItemSet,
+}
+
+impl FromStr for Rucksack {
+    type Err = ParseRucksackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.len() % 2 != 0 || !s.is_ascii() {
+            return Err(ParseRucksackError);
+        }
+        let (first, second) = s.split_at(s.len() / 2);
+        Ok(Rucksack {
+            first: ItemSet::from_items(first).ok_or(ParseRucksackError)?,
+            second: ItemSet::from_items(second).ok_or(ParseRucksackError)?,
+        })
+    }
+}
+
+impl Rucksack {
+    /// Return all items in the rucksack.
+    pub fn items(&self) -> ItemSet {
+        ItemSet(self.first.0 | self.second.0)
+    }
+
+    /// Return the items present in both compartments.
+    pub fn common_items(&self) -> ItemSet {
+        self.first.intersection(&self.second)
+    }
+
+    /// Return the single item present in both compartments.
+    pub fn misplaced_item(&self) -> Result<char, CommonItemError> {
+        self.common_items().single()
+    }
+}
+
+/// Return the items carried by every rucksack in the group.
+pub fn common_items(group: &[Rucksack]) -> ItemSet {
+    group
+        .iter()
+        .map(|r| r.items())
+        .reduce(|a, b| a.intersection(&b))
+        .unwrap_or_default()
+}
+
+/// Return the single item carried by every rucksack in the group.
+pub fn badge(group: &[Rucksack]) -> Result<char, CommonItemError> {
+    common_items(group).single()
+}
+
+/// Parse the rucksack on each line of the puzzle input.
+pub fn parse(s: &str) -> Result<Vec<Rucksack>, ParseRucksackError> {
+    s.trim().lines().map(str::parse).collect()
 }
 
-pub fn sum_priorities_p1(s: &str) -> i64 {
-    s.trim().split_terminator('\n').map(parse_line).sum()
+/// Return the sum of badge priorities for consecutive groups of the given
+/// size, which must divide the number of rucksacks.
+pub fn sum_badge_priorities(
+    rucksacks: &[Rucksack],
+    group_size: usize,
+) -> Result<i64, CommonItemError> {
+    if group_size == 0 || rucksacks.len() % group_size != 0 {
+        return Err(CommonItemError::InvalidGroupSize(group_size));
+    }
+    rucksacks
+        .chunks(group_size)
+        .map(|group| badge(group).map(priority))
+        .sum()
+}
+
+pub fn part1(rucksacks: &[Rucksack]) -> i64 {
+    rucksacks
+        .iter()
+        .map(|r| {
+            r.misplaced_item()
+                .map(priority)
+                .unwrap_or_else(|e| panic!("no single misplaced item: {:?}", e))
+        })
+        .sum()
 }
 
-pub fn sum_priorities_p2(s: &str) -> i64 {
-    let v: Vec<&str> = s.lines().collect();
-    let chunks: Vec<&[&str]> = v.chunks(3).collect();
-    let mut sum = 0;
-    for chunk in chunks {
-        let mut s0: HashSet<char> = chunk[0].chars().collect();
-        let s1: HashSet<char> = chunk[1].chars().collect();
-        let s2: HashSet<char> = chunk[2].chars().collect();
-        s0.retain(|e| s1.contains(e));
-        s0.retain(|e| s2.contains(e));
-        let ch = s0.iter().next().unwrap();
-        sum += priority(*ch);
-    }
-    sum
+pub fn part2(rucksacks: &[Rucksack]) -> i64 {
+    sum_badge_priorities(rucksacks, 3).unwrap_or_else(|e| panic!("no single badge: {:?}", e))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_sum_priorities_p1() {
-        let s = "vJrwpWtwJgWrhcsFMMfFFhFp
+    static INPUT: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
 PmmdzqPrVvPwwTWBwg
 wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
 ttgJtRGJQctTZtZT
 CrZsJsPPZsGzwwsLwLmpwMDw";
-        assert_eq!(sum_priorities_p1(s), 157);
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 157);
     }
 
     #[test]
-    fn test_sum_priorities_p2() {
-        let s = "vJrwpWtwJgWrhcsFMMfFFhFp
-jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
-PmmdzqPrVvPwwTWBwg
-wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
-ttgJtRGJQctTZtZT
-CrZsJsPPZsGzwwsLwLmpwMDw";
-        assert_eq!(sum_priorities_p2(s), 70);
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 70);
+    }
+
+    #[test]
+    fn test_common_items() {
+        let rucksacks = parse(INPUT).unwrap();
+        assert_eq!(rucksacks[0].misplaced_item(), Ok('p'));
+        assert_eq!(
+            common_items(&rucksacks[..2]).iter().collect::<String>(),
+            "frsFM"
+        );
+        assert_eq!(
+            badge(&rucksacks[..2]).unwrap_err(),
+            CommonItemError::MultipleShared(common_items(&rucksacks[..2]))
+        );
+        assert_eq!(badge(&rucksacks), Err(CommonItemError::NoneShared));
+        assert_eq!(badge(&[]), Err(CommonItemError::NoneShared));
+        assert_eq!(
+            sum_badge_priorities(&rucksacks, 6),
+            Err(CommonItemError::NoneShared)
+        );
+        assert_eq!(
+            sum_badge_priorities(&rucksacks, 0),
+            Err(CommonItemError::InvalidGroupSize(0))
+        );
+        assert_eq!(
+            sum_badge_priorities(&rucksacks, 4),
+            Err(CommonItemError::InvalidGroupSize(4))
+        );
+        assert_eq!("abc".parse::<Rucksack>(), Err(ParseRucksackError));
+        assert_eq!("ab1c".parse::<Rucksack>(), Err(ParseRucksackError));
     }
 }
@@ -1,60 +1,212 @@
 use std::str::FromStr;
 
-struct Range {
-    start: i64,
-    end: i64,
+/// An inclusive range of section IDs.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseRangeError {
+    Malformed,
+    Inverted,
 }
 
 impl FromStr for Range {
-    type Err = ();
+    type Err = ParseRangeError;
 
     fn from_str(s: &str) -> Result<Range, Self::Err> {
         let v: Vec<&str> = s.trim().split('-').collect();
         if v.len() != 2 {
-            return Err(());
+            return Err(ParseRangeError::Malformed);
         }
 
         if let (Ok(x), Ok(y)) = (v[0].parse::<i64>(), v[1].parse::<i64>()) {
+            if x > y {
+                return Err(ParseRangeError::Inverted);
+            }
             Ok(Range { start: x, end: y })
         } else {
-            Err(())
+            Err(ParseRangeError::Malformed)
         }
     }
 }
 
 impl Range {
-    fn does_fully_contain(&self, r: &Range) -> bool {
+    pub fn does_fully_contain(&self, r: &Range) -> bool {
         (r.start >= self.start && r.end <= self.end) || (self.start >= r.start && self.end <= r.end)
     }
 
-    fn does_overlap(&self, r: &Range) -> bool {
+    pub fn does_overlap(&self, r: &Range) -> bool {
         let x = if self.start < r.start { self } else { r };
         let y = if self.start > r.start { self } else { r };
         x.end >= y.start
     }
+
+    pub fn len(&self) -> i64 {
+        self.end - self.start + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() <= 0
+    }
+}
+
+/// A set of section IDs, stored as sorted, disjoint and non-adjacent ranges.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct RangeSet {
+    ranges: Vec<Range>,
+}
+
+impl From<Range> for RangeSet {
+    fn from(r: Range) -> Self {
+        RangeSet::from_iter([r])
+    }
 }
 
-fn parse_line(s: &str) -> (Range, Range) {
-    let v: Vec<&str> = s.trim().split(',').collect();
-    (v[0].parse().unwrap(), v[1].parse().unwrap())
+impl FromIterator<Range> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = Range>>(iter: I) -> Self {
+        let mut ranges: Vec<Range> = iter.into_iter().filter(|r| !r.is_empty()).collect();
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+        for r in ranges {
+            match merged.last_mut() {
+                Some(last) if r.start <= last.end + 1 => last.end = last.end.max(r.end),
+                _ => merged.push(r),
+            }
+        }
+        RangeSet { ranges: merged }
+    }
 }
 
-pub fn num_fully_contained(s: &str) -> i64 {
-    s.trim()
-        .split_terminator('\n')
-        .map(parse_line)
-        .map(|(r1, r2)| r1.does_fully_contain(&r2))
+impl RangeSet {
+    pub fn ranges(&self) -> &[Range] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn contains(&self, id: i64) -> bool {
+        self.ranges.iter().any(|r| r.start <= id && id <= r.end)
+    }
+
+    /// Return the number of section IDs in the set.
+    pub fn len(&self) -> i64 {
+        self.ranges.iter().map(|r| r.len()).sum()
+    }
+
+    /// Return the smallest range covering the whole set.
+    pub fn hull(&self) -> Option<Range> {
+        Some(Range {
+            start: self.ranges.first()?.start,
+            end: self.ranges.last()?.end,
+        })
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        self.ranges
+            .iter()
+            .chain(other.ranges.iter())
+            .copied()
+            .collect()
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let r = Range {
+                start: a.start.max(b.start),
+                end: a.end.min(b.end),
+            };
+            if !r.is_empty() {
+                ranges.push(r);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for &a in &self.ranges {
+            let mut start = a.start;
+            while j < other.ranges.len() && other.ranges[j].end < start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start <= a.end {
+                let b = other.ranges[k];
+                if b.start > start {
+                    ranges.push(Range {
+                        start,
+                        end: b.start - 1,
+                    });
+                }
+                start = start.max(b.end + 1);
+                k += 1;
+            }
+            if start <= a.end {
+                ranges.push(Range { start, end: a.end });
+            }
+        }
+        RangeSet { ranges }
+    }
+}
+
+fn parse_line(s: &str) -> Result<(Range, Range), ParseRangeError> {
+    let (r1, r2) = s.trim().split_once(',').ok_or(ParseRangeError::Malformed)?;
+    Ok((r1.parse()?, r2.parse()?))
+}
+
+/// Parse the pair of section assignments on each line of the puzzle input.
+pub fn parse(s: &str) -> Result<Vec<(Range, Range)>, ParseRangeError> {
+    s.trim().lines().map(parse_line).collect()
+}
+
+/// Return the sections assigned to at least one elf.
+pub fn assigned_sections(s: &str) -> Result<RangeSet, ParseRangeError> {
+    let mut ranges = Vec::new();
+    for line in s.trim().lines() {
+        let (r1, r2) = parse_line(line)?;
+        ranges.extend([r1, r2]);
+    }
+    Ok(ranges.into_iter().collect())
+}
+
+/// Return the sections between the lowest and highest assigned ones that no
+/// elf is assigned to.
+pub fn unassigned_sections(s: &str) -> Result<RangeSet, ParseRangeError> {
+    let assigned = assigned_sections(s)?;
+    Ok(match assigned.hull() {
+        Some(hull) => RangeSet::from(hull).difference(&assigned),
+        None => RangeSet::default(),
+    })
+}
+
+pub fn part1(assignments: &[(Range, Range)]) -> i64 {
+    assignments
+        .iter()
+        .map(|(r1, r2)| r1.does_fully_contain(r2))
         .filter(|b| *b)
         .count()
         .try_into()
         .unwrap()
 }
 
-pub fn num_overlapping(s: &str) -> i64 {
-    s.trim()
-        .split_terminator('\n')
-        .map(parse_line)
-        .map(|(r1, r2)| r1.does_overlap(&r2))
+pub fn part2(assignments: &[(Range, Range)]) -> i64 {
+    assignments
+        .iter()
+        .map(|(r1, r2)| r1.does_overlap(r2))
         .filter(|b| *b)
         .count()
         .try_into()
@@ -65,25 +217,51 @@ pub fn num_overlapping(s: &str) -> i64 {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_num_fully_contained() {
-        let s = "2-4,6-8
+    static INPUT: &str = "2-4,6-8
 2-3,4-5
 5-7,7-9
 2-8,3-7
 6-6,4-6
 2-6,4-8";
-        assert_eq!(num_fully_contained(s), 2);
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 2);
     }
 
     #[test]
-    fn test_num_overlapping() {
-        let s = "2-4,6-8
-2-3,4-5
-5-7,7-9
-2-8,3-7
-6-6,4-6
-2-6,4-8";
-        assert_eq!(num_overlapping(s), 4);
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 4);
+    }
+
+    #[test]
+    fn test_range_set() {
+        let set = |ranges: &[(i64, i64)]| -> RangeSet {
+            ranges
+                .iter()
+                .map(|&(start, end)| Range { start, end })
+                .collect()
+        };
+        let a = set(&[(1, 3), (4, 6), (10, 12)]);
+        let b = set(&[(2, 4), (11, 20)]);
+        assert_eq!(a, set(&[(1, 6), (10, 12)]));
+        assert_eq!(a.len(), 9);
+        assert_eq!(a.union(&b), set(&[(1, 6), (10, 20)]));
+        assert_eq!(a.intersection(&b), set(&[(2, 4), (11, 12)]));
+        assert_eq!(a.difference(&b), set(&[(1, 1), (5, 6), (10, 10)]));
+        assert_eq!(b.difference(&a), set(&[(13, 20)]));
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn test_assigned_sections() {
+        assert_eq!(assigned_sections(INPUT).unwrap().len(), 8);
+        let unassigned = unassigned_sections("1-2,4-4\n8-9,3-3").unwrap();
+        assert_eq!(unassigned.ranges(), &[Range { start: 5, end: 7 }]);
+        assert_eq!(assigned_sections("2-4,7-3"), Err(ParseRangeError::Inverted));
+        assert_eq!(
+            assigned_sections("2-4;6-8"),
+            Err(ParseRangeError::Malformed)
+        );
     }
 }
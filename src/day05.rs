@@ -15,13 +15,13 @@ fn parse_stack_row(n: usize, s: &str) -> Vec<Option<char>> {
 }
 
 // Parse a step and return number of crates, from stack and to stack.
-fn parse_step(s: &str) -> (usize, usize, usize) {
+fn parse_step(s: &str) -> Result<(usize, usize, usize), ParseProcedureError> {
     let v: Vec<&str> = s.split(' ').collect();
-    (
-        v[1].parse().unwrap(),
-        v[3].parse().unwrap(),
-        v[5].parse().unwrap(),
-    )
+    if v.len() != 6 {
+        return Err(ParseProcedureError);
+    }
+    let number = |s: &str| s.parse().map_err(|_| ParseProcedureError);
+    Ok((number(v[1])?, number(v[3])?, number(v[5])?))
 }
 
 // Parse stack representation and return a vector of stacks.
@@ -46,10 +46,10 @@ fn parse_stacks(s: &str) -> Vec<Vec<char>> {
 }
 
 // An Executor can execute the given steps on the given stacks.
-type Executor = fn(&Vec<(usize, usize, usize)>, &mut [Vec<char>]);
+type Executor = fn(&[(usize, usize, usize)], &mut [Vec<char>]);
 
 // Execute steps according to part 1 of the problem definition.
-fn execute_steps_p1(steps: &Vec<(usize, usize, usize)>, stacks: &mut [Vec<char>]) {
+fn execute_steps_p1(steps: &[(usize, usize, usize)], stacks: &mut [Vec<char>]) {
     for (n, from, to) in steps {
         for _ in 0..*n {
             let ch = stacks[from - 1].pop();
@@ -59,7 +59,7 @@ fn execute_steps_p1(steps: &Vec<(usize, usize, usize)>, stacks: &mut [Vec<char>]
 }
 
 // Execute steps according to part 2 of the problem defiition.
-fn execute_steps_p2(steps: &Vec<(usize, usize, usize)>, stacks: &mut [Vec<char>]) {
+fn execute_steps_p2(steps: &[(usize, usize, usize)], stacks: &mut [Vec<char>]) {
     for (n, from, to) in steps {
         let from_len = stacks[from - 1].len();
         let mut drained: Vec<char> = stacks[from - 1].drain(from_len - n..).collect();
@@ -67,27 +67,49 @@ fn execute_steps_p2(steps: &Vec<(usize, usize, usize)>, stacks: &mut [Vec<char>]
     }
 }
 
-// Parse stacks, steps and compute top of the stack after executing steps.
-pub fn top_of_stack(s: &str, executor: Executor) -> String {
-    let Some((stacks_str, steps_str)) = s.split_once("\n\n") else {
-        unreachable!();
-    };
-    let mut stacks: Vec<Vec<char>> = parse_stacks(stacks_str);
-    let steps = steps_str.trim().split('\n').map(parse_step).collect();
-    executor(&steps, &mut stacks);
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseProcedureError;
+
+/// The starting stacks of crates and the steps of the rearrangement procedure.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Procedure {
+    stacks: Vec<Vec<char>>,
+    steps: Vec<(usize, usize, usize)>,
+}
+
+/// Parse the stacks and the steps of the procedure.
+pub fn parse(s: &str) -> Result<Procedure, ParseProcedureError> {
+    let (stacks_str, steps_str) = s.split_once("\n\n").ok_or(ParseProcedureError)?;
+    let stacks = parse_stacks(stacks_str);
+    let steps: Vec<(usize, usize, usize)> = steps_str
+        .trim()
+        .split('\n')
+        .map(parse_step)
+        .collect::<Result<_, _>>()?;
+    let valid = |i: usize| (1..=stacks.len()).contains(&i);
+    if !steps.iter().all(|&(_, from, to)| valid(from) && valid(to)) {
+        return Err(ParseProcedureError);
+    }
+    Ok(Procedure { stacks, steps })
+}
+
+// Compute top of the stack after executing steps.
+fn top_of_stack(procedure: &Procedure, executor: Executor) -> String {
+    let mut stacks = procedure.stacks.clone();
+    executor(&procedure.steps, &mut stacks);
     stacks.iter().map(|v| v.last().unwrap()).collect()
 }
 
-// Parse stacks, steps and compute top of tack after executing steps according
-// to part 1 of the problem.
-pub fn top_of_stack_p1(s: &str) -> String {
-    top_of_stack(s, execute_steps_p1)
+// Compute top of stack after executing steps according to part 1 of the
+// problem.
+pub fn part1(procedure: &Procedure) -> String {
+    top_of_stack(procedure, execute_steps_p1)
 }
 
-// Parse stacks, steps and compute top of tack after executing steps according
-// to part 2 of the problem.
-pub fn top_of_stack_p2(s: &str) -> String {
-    top_of_stack(s, execute_steps_p2)
+// Compute top of stack after executing steps according to part 2 of the
+// problem.
+pub fn part2(procedure: &Procedure) -> String {
+    top_of_stack(procedure, execute_steps_p2)
 }
 
 #[cfg(test)]
@@ -95,7 +117,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_top_of_stack_p1() {
+    fn test_part1() {
         let s = "    [D]
 [N] [C]
 [Z] [M] [P]
@@ -105,11 +127,11 @@ move 1 from 2 to 1
 move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
-        assert_eq!(top_of_stack_p1(s), "CMZ");
+        assert_eq!(part1(&parse(s).unwrap()), "CMZ");
     }
 
     #[test]
-    fn test_top_of_stack_p2() {
+    fn test_part2() {
         let s = "    [D]
 [N] [C]
 [Z] [M] [P]
@@ -119,6 +141,6 @@ move 1 from 2 to 1
 move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
-        assert_eq!(top_of_stack_p2(s), "MCD");
+        assert_eq!(part2(&parse(s).unwrap()), "MCD");
     }
 }
@@ -1,24 +1,99 @@
-use std::collections::HashSet;
+use std::io::{self, BufReader, Read};
 
-// Find start of packet with n unique characters.
-fn start_of_packet(s: &str, n: usize) -> usize {
-    let bytes = s.as_bytes();
-    for i in n..s.len() {
-        if HashSet::<&u8>::from_iter(bytes[i - n..i].iter()).len() == n {
-            return i;
+/// Incrementally detects markers of `n` distinct bytes in a datastream.
+///
+/// Keeps the position each byte value was last seen at, so the start of the
+/// current run of distinct bytes is updated in constant time per byte.
+#[derive(Debug, Clone)]
+pub struct MarkerDetector {
+    n: usize,
+    // One-based position each byte was last seen at, zero if never seen.
+    last_seen: [usize; 256],
+    // Number of bytes before the current run of distinct bytes.
+    run_start: usize,
+    position: usize,
+}
+
+impl MarkerDetector {
+    pub fn new(n: usize) -> Self {
+        MarkerDetector {
+            n,
+            last_seen: [0; 256],
+            run_start: 0,
+            position: 0,
         }
     }
-    0
+
+    /// Feed the next byte of the stream. Returns the number of bytes
+    /// processed so far if the last `n` of them are all different.
+    pub fn push(&mut self, b: u8) -> Option<usize> {
+        self.position += 1;
+        self.run_start = self.run_start.max(self.last_seen[b as usize]);
+        self.last_seen[b as usize] = self.position;
+        (self.position - self.run_start >= self.n).then_some(self.position)
+    }
+}
+
+/// Return the end positions of all markers with `n` distinct characters.
+pub fn find_markers(s: &str, n: usize) -> Vec<usize> {
+    let mut detector = MarkerDetector::new(n);
+    s.trim_end()
+        .bytes()
+        .filter_map(|b| detector.push(b))
+        .collect()
+}
+
+/// Lazily find the end positions of all markers with `n` distinct bytes in
+/// the given reader, without loading the whole stream into memory. Like
+/// `find_markers`, trailing whitespace such as the newline ending an input
+/// file is not part of the stream.
+pub fn markers<R: Read>(reader: R, n: usize) -> impl Iterator<Item = io::Result<usize>> {
+    let mut detector = MarkerDetector::new(n);
+    // Whitespace read since the last other byte, only fed to the detector once
+    // it turns out not to be trailing.
+    let mut pending = Vec::new();
+    BufReader::new(reader).bytes().flat_map(move |b| match b {
+        Ok(b) if b.is_ascii_whitespace() => {
+            pending.push(b);
+            Vec::new()
+        }
+        Ok(b) => {
+            pending.push(b);
+            pending
+                .drain(..)
+                .filter_map(|b| detector.push(b).map(Ok))
+                .collect()
+        }
+        Err(e) => vec![Err(e)],
+    })
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseDatastreamError;
+
+/// Parse the datastream, which must fit on a single line.
+pub fn parse(s: &str) -> Result<Vec<u8>, ParseDatastreamError> {
+    let s = s.trim_end();
+    if s.contains('\n') {
+        return Err(ParseDatastreamError);
+    }
+    Ok(s.as_bytes().to_vec())
+}
+
+// Find start of packet with n unique characters.
+pub fn start_of_packet(datastream: &[u8], n: usize) -> Option<usize> {
+    let mut detector = MarkerDetector::new(n);
+    datastream.iter().find_map(|&b| detector.push(b))
 }
 
 // Find start of packet according to part 1 of the problem.
-pub fn start_of_packet_p1(s: &str) -> usize {
-    start_of_packet(s, 4)
+pub fn part1(datastream: &[u8]) -> Option<usize> {
+    start_of_packet(datastream, 4)
 }
 
 // Find start of packet according to part 2 of the problem.
-pub fn start_of_packet_p2(s: &str) -> usize {
-    start_of_packet(s, 14)
+pub fn part2(datastream: &[u8]) -> Option<usize> {
+    start_of_packet(datastream, 14)
 }
 
 #[cfg(test)]
@@ -27,18 +102,70 @@ mod tests {
 
     #[test]
     fn test_start_of_packet_4() {
-        assert_eq!(start_of_packet_p1("bvwbjplbgvbhsrlpgdmjqwftvncz"), 5);
-        assert_eq!(start_of_packet_p1("nppdvjthqldpwncqszvftbrmjlhg"), 6);
-        assert_eq!(start_of_packet_p1("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 10);
-        assert_eq!(start_of_packet_p1("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 11);
+        assert_eq!(
+            part1(&parse("bvwbjplbgvbhsrlpgdmjqwftvncz").unwrap()),
+            Some(5)
+        );
+        assert_eq!(
+            part1(&parse("nppdvjthqldpwncqszvftbrmjlhg").unwrap()),
+            Some(6)
+        );
+        assert_eq!(
+            part1(&parse("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").unwrap()),
+            Some(10)
+        );
+        assert_eq!(
+            part1(&parse("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").unwrap()),
+            Some(11)
+        );
     }
 
     #[test]
     fn test_start_of_packet_14() {
-        assert_eq!(start_of_packet_p2("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), 19);
-        assert_eq!(start_of_packet_p2("bvwbjplbgvbhsrlpgdmjqwftvncz"), 23);
-        assert_eq!(start_of_packet_p2("nppdvjthqldpwncqszvftbrmjlhg"), 23);
-        assert_eq!(start_of_packet_p2("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 29);
-        assert_eq!(start_of_packet_p2("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 26);
+        assert_eq!(
+            part2(&parse("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap()),
+            Some(19)
+        );
+        assert_eq!(
+            part2(&parse("bvwbjplbgvbhsrlpgdmjqwftvncz").unwrap()),
+            Some(23)
+        );
+        assert_eq!(
+            part2(&parse("nppdvjthqldpwncqszvftbrmjlhg").unwrap()),
+            Some(23)
+        );
+        assert_eq!(
+            part2(&parse("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").unwrap()),
+            Some(29)
+        );
+        assert_eq!(
+            part2(&parse("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").unwrap()),
+            Some(26)
+        );
+    }
+
+    #[test]
+    fn test_no_marker() {
+        assert_eq!(part1(&parse("abcabcabc").unwrap()), None);
+        assert_eq!(part1(&parse("").unwrap()), None);
+        assert_eq!(part1(&parse("aabcd").unwrap()), Some(5));
+    }
+
+    #[test]
+    fn test_markers() {
+        assert_eq!(parse("ab\ncd"), Err(ParseDatastreamError));
+        assert_eq!(find_markers("aabcdab", 4), vec![5, 6, 7]);
+        let stream = "mjqjpqmgbljsphdztnvjfqwrcgsmlb".repeat(1000);
+        let from_reader: Vec<usize> = markers(stream.as_bytes(), 14)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(from_reader, find_markers(&stream, 14));
+        assert_eq!(from_reader[0], 19);
+        for s in ["aabcd\n", " ab cd ef\n\n"] {
+            let from_file: Vec<usize> =
+                markers(s.as_bytes(), 4).collect::<io::Result<_>>().unwrap();
+            assert_eq!(from_file, find_markers(s, 4));
+        }
+        assert_eq!(find_markers(" ab cd ef\n\n", 4), vec![5, 6, 8, 9]);
     }
 }
@@ -1,28 +1,31 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-fn parse_output(s: &str) -> Vec<usize> {
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseOutputError;
+
+/// Parse the terminal output and return the total size of every directory,
+/// smallest first.
+pub fn parse(s: &str) -> Result<Vec<usize>, ParseOutputError> {
     let mut sizes: HashMap<PathBuf, usize> = HashMap::new();
     let mut path = PathBuf::new();
 
     let lines: Vec<&str> = s.trim().split('\n').collect();
     for line in lines {
         if line.starts_with("$ cd ") {
-            let Some((_, dir_name)) = line.rsplit_once(' ') else {
-                unreachable!();
-            };
+            let (_, dir_name) = line.rsplit_once(' ').ok_or(ParseOutputError)?;
             if dir_name == ".." {
                 path.pop();
             } else {
-                path.push(dir_name)
+                path.push(dir_name);
+                // Record directories even if they hold no files.
+                sizes.entry(path.clone()).or_insert(0);
             }
         } else if line.starts_with("$ ls") || line.starts_with("dir ") {
             // Do nothing.
         } else {
-            let Some((size_str, _)) = line.split_once(' ') else {
-                unreachable!();
-            };
-            let size: usize = size_str.parse().unwrap();
+            let (size_str, _) = line.split_once(' ').ok_or(ParseOutputError)?;
+            let size: usize = size_str.parse().map_err(|_| ParseOutputError)?;
             let mut p = path.clone();
             loop {
                 *(sizes.entry(p.clone()).or_insert(0)) += size;
@@ -35,20 +38,23 @@ fn parse_output(s: &str) -> Vec<usize> {
 
     let mut v = Vec::from_iter(sizes.values().cloned());
     v.sort();
-    v
+    Ok(v)
 }
 
-pub fn total_size_p1(s: &str) -> usize {
-    let v = parse_output(s);
-    v.iter().filter(|&&s| s <= 100000).sum()
+pub const MAX_SIZE: usize = 100000;
+pub const DISK_SIZE: usize = 70000000;
+pub const REQUIRED: usize = 30000000;
+
+/// Return the total size of the directories of at most `max_size`.
+pub fn total_size_at_most(v: &[usize], max_size: usize) -> usize {
+    v.iter().filter(|&&s| s <= max_size).sum()
 }
 
-pub fn total_size_p2(s: &str) -> usize {
-    let v = parse_output(s);
-    let total = 70000000;
-    let required = 30000000;
+/// Return the size of the smallest directory to delete so that a disk of
+/// `disk_size` has `required` space unused.
+pub fn smallest_to_delete(v: &[usize], disk_size: usize, required: usize) -> usize {
     let used = v.last().unwrap();
-    let unused = total - used;
+    let unused = disk_size.saturating_sub(*used);
     *v.iter()
         .filter(|&&s| s + unused >= required)
         .take(1)
@@ -56,12 +62,21 @@ pub fn total_size_p2(s: &str) -> usize {
         .unwrap()
 }
 
+pub fn part1(v: &[usize]) -> usize {
+    total_size_at_most(v, MAX_SIZE)
+}
+
+pub fn part2(v: &[usize]) -> usize {
+    smallest_to_delete(v, DISK_SIZE, REQUIRED)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
-    fn test_total_size_p1() {
+    fn test_part1() {
         let input = "$ cd /
 $ ls
 dir a
@@ -85,11 +100,22 @@ $ ls
 8033020 d.log
 5626152 d.ext
 7214296 k";
-        assert_eq!(total_size_p1(input), 95437)
+        assert_eq!(part1(&parse(input).unwrap()), 95437)
+    }
+
+    #[test]
+    fn test_empty_directory() {
+        let input = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+$ cd a
+$ ls";
+        assert_eq!(parse(input), Ok(vec![0, 14848514]));
     }
 
     #[test]
-    fn test_total_size_p2() {
+    fn test_part2() {
         let input = "$ cd /
 $ ls
 dir a
@@ -113,6 +139,83 @@ $ ls
 8033020 d.log
 5626152 d.ext
 7214296 k";
-        assert_eq!(total_size_p2(input), 24933642)
+        assert_eq!(part2(&parse(input).unwrap()), 24933642)
+    }
+
+    // A directory tree as a parent per directory (the root has none) and the
+    // file sizes in each directory.
+    #[derive(Debug, Clone)]
+    struct Tree {
+        parents: Vec<usize>,
+        files: Vec<Vec<usize>>,
+    }
+
+    impl Tree {
+        fn children(&self, dir: usize) -> Vec<usize> {
+            (1..self.parents.len())
+                .filter(|&d| self.parents[d] == dir)
+                .collect()
+        }
+
+        fn size(&self, dir: usize) -> usize {
+            self.files[dir].iter().sum::<usize>()
+                + self
+                    .children(dir)
+                    .iter()
+                    .map(|&c| self.size(c))
+                    .sum::<usize>()
+        }
+
+        // Return the terminal output of exploring the tree depth first.
+        fn transcript(&self, dir: usize, lines: &mut Vec<String>) {
+            lines.push(String::from("$ ls"));
+            for child in self.children(dir) {
+                lines.push(format!("dir d{}", child));
+            }
+            for (i, size) in self.files[dir].iter().enumerate() {
+                lines.push(format!("{} f{}.txt", size, i));
+            }
+            for child in self.children(dir) {
+                lines.push(format!("$ cd d{}", child));
+                self.transcript(child, lines);
+                lines.push(String::from("$ cd .."));
+            }
+        }
+    }
+
+    // Generate trees of up to 8 directories holding at most 60000000 bytes.
+    fn tree() -> impl Strategy<Value = Tree> {
+        (1usize..=8)
+            .prop_flat_map(|n| {
+                (
+                    prop::collection::vec(any::<prop::sample::Index>(), n),
+                    prop::collection::vec(prop::collection::vec(1usize..1500000, 0..5), n),
+                )
+            })
+            .prop_map(|(parents, files)| Tree {
+                parents: parents
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| if i == 0 { 0 } else { p.index(i) })
+                    .collect(),
+                files,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_total_size(tree in tree()) {
+            let mut lines = vec![String::from("$ cd /")];
+            tree.transcript(0, &mut lines);
+            let input = lines.join("\n");
+
+            let sizes: Vec<usize> = (0..tree.parents.len()).map(|d| tree.size(d)).collect();
+            let expected_p1: usize = sizes.iter().filter(|&&s| s <= 100000).sum();
+            let needed = 30000000 - (70000000 - sizes[0]).min(30000000);
+            let expected_p2 = *sizes.iter().filter(|&&s| s >= needed).min().unwrap();
+            let sizes = parse(&input).unwrap();
+            prop_assert_eq!(part1(&sizes), expected_p1);
+            prop_assert_eq!(part2(&sizes), expected_p2);
+        }
     }
 }
@@ -1,79 +1,129 @@
-pub fn num_visible_trees(s: &str) -> usize {
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseGridError;
+
+/// Parse the height of each tree, row by row.
+pub fn parse(s: &str) -> Result<Vec<Vec<u8>>, ParseGridError> {
     let trees: Vec<Vec<u8>> = s
         .trim()
         .split('\n')
-        .map(|l| l.as_bytes().iter().map(|b| b - b'0').collect())
-        .collect();
-    let num_rows = trees.len();
-    let num_columns = trees[0].len();
-    let mut visible = vec![vec![false; num_columns]; num_rows];
+        .map(|l| {
+            l.bytes()
+                .map(|b| b.is_ascii_digit().then(|| b - b'0').ok_or(ParseGridError))
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    if trees[0].is_empty() || trees.iter().any(|row| row.len() != trees[0].len()) {
+        return Err(ParseGridError);
+    }
+    Ok(trees)
+}
 
-    for i in 0..num_rows {
-        for j in 0..num_columns {
+// Return the number of trees of the given row visible from outside the grid.
+fn num_visible_in_row(trees: &[Vec<u8>], i: usize) -> usize {
+    (0..trees[i].len())
+        .filter(|&j| {
             let height = trees[i][j];
             // Courtesy: https://github.com/Basicprogrammer10/Advent-Of-Code/blob/master/src/solutions/year_2022/day_08.rs
-            visible[i][j] = trees[..i].iter().all(|v| v[j] < height)
+            trees[..i].iter().all(|v| v[j] < height)
                 || trees[i][..j].iter().all(|&t| t < height)
                 || trees[i + 1..].iter().all(|v| v[j] < height)
-                || trees[i][j + 1..].iter().all(|&t| t < height);
-        }
-    }
-
-    visible.iter().fold(0, |acc, v| {
-        acc + v.iter().fold(0, |acc, &p| if p { acc + 1 } else { acc })
-    })
+                || trees[i][j + 1..].iter().all(|&t| t < height)
+        })
+        .count()
 }
 
-pub fn highest_scenic_score(s: &str) -> usize {
-    let trees: Vec<Vec<u8>> = s
-        .trim()
-        .split('\n')
-        .map(|l| l.as_bytes().iter().map(|b| b - b'0').collect())
-        .collect();
+// Return the highest scenic score of the trees of the given row.
+fn best_score_in_row(trees: &[Vec<u8>], i: usize) -> usize {
     let num_rows = trees.len();
     let num_columns = trees[0].len();
-    let mut score = vec![vec![0; num_columns]; num_rows];
-
-    for i in 0..num_rows {
-        for j in 0..num_columns {
-            let mut top = 0;
-            for k in (0..i).rev() {
-                top += 1;
-                if trees[i][j] <= trees[k][j] {
-                    break;
-                }
+    let mut best = 0;
+
+    for j in 0..num_columns {
+        let mut top = 0;
+        for k in (0..i).rev() {
+            top += 1;
+            if trees[i][j] <= trees[k][j] {
+                break;
             }
+        }
 
-            let mut left = 0;
-            for k in (0..j).rev() {
-                left += 1;
-                if trees[i][j] <= trees[i][k] {
-                    break;
-                }
+        let mut left = 0;
+        for k in (0..j).rev() {
+            left += 1;
+            if trees[i][j] <= trees[i][k] {
+                break;
             }
+        }
 
-            let mut bottom = 0;
-            for k in i + 1..num_rows {
-                bottom += 1;
-                if trees[i][j] <= trees[k][j] {
-                    break;
-                }
+        let mut bottom = 0;
+        for k in i + 1..num_rows {
+            bottom += 1;
+            if trees[i][j] <= trees[k][j] {
+                break;
             }
+        }
 
-            let mut right = 0;
-            for k in j + 1..num_columns {
-                right += 1;
-                if trees[i][j] <= trees[i][k] {
-                    break;
-                }
+        let mut right = 0;
+        for k in j + 1..num_columns {
+            right += 1;
+            if trees[i][j] <= trees[i][k] {
+                break;
             }
-            score[i][j] = top * left * bottom * right;
         }
+        best = best.max(top * left * bottom * right);
     }
+    best
+}
+
+/// Count the visible trees one row after the other.
+pub fn part1_sequential(trees: &[Vec<u8>]) -> usize {
+    (0..trees.len()).map(|i| num_visible_in_row(trees, i)).sum()
+}
+
+/// Count the visible trees of every row in parallel.
+#[cfg(feature = "parallel")]
+pub fn part1_parallel(trees: &[Vec<u8>]) -> usize {
+    (0..trees.len())
+        .into_par_iter()
+        .map(|i| num_visible_in_row(trees, i))
+        .sum()
+}
+
+/// Find the highest scenic score one row after the other.
+pub fn part2_sequential(trees: &[Vec<u8>]) -> usize {
+    (0..trees.len())
+        .map(|i| best_score_in_row(trees, i))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Find the highest scenic score of every row in parallel.
+#[cfg(feature = "parallel")]
+pub fn part2_parallel(trees: &[Vec<u8>]) -> usize {
+    (0..trees.len())
+        .into_par_iter()
+        .map(|i| best_score_in_row(trees, i))
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn part1(trees: &[Vec<u8>]) -> usize {
+    #[cfg(feature = "parallel")]
+    let count = part1_parallel;
+    #[cfg(not(feature = "parallel"))]
+    let count = part1_sequential;
+    count(trees)
+}
 
-    score
-        .iter()
-        .fold(0, |acc, v| acc.max(v.iter().fold(0, |acc, &s| acc.max(s))))
+pub fn part2(trees: &[Vec<u8>]) -> usize {
+    #[cfg(feature = "parallel")]
+    let find = part2_parallel;
+    #[cfg(not(feature = "parallel"))]
+    let find = part2_sequential;
+    find(trees)
 }
 
 #[cfg(test)]
@@ -81,22 +131,27 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_num_visible_trees() {
+    fn test_part1() {
         let input = "30373
 25512
 65332
 33549
 35390";
-        assert_eq!(num_visible_trees(input), 21)
+        assert_eq!(part1(&parse(input).unwrap()), 21);
+        #[cfg(feature = "parallel")]
+        assert_eq!(part1_parallel(&parse(input).unwrap()), 21);
     }
 
     #[test]
-    fn test_highest_scenic_score() {
+    fn test_part2() {
         let input = "30373
 25512
 65332
 33549
 35390";
-        assert_eq!(highest_scenic_score(input), 8)
+        assert_eq!(part2(&parse(input).unwrap()), 8);
+        #[cfg(feature = "parallel")]
+        assert_eq!(part2_parallel(&parse(input).unwrap()), 8);
+        assert_eq!(parse("303\n25"), Err(ParseGridError));
     }
 }
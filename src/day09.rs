@@ -1,24 +1,27 @@
 use std::collections::HashSet;
 use std::str::FromStr;
 
-#[derive(Debug)]
-enum Command {
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Command {
     Left(usize),
     Right(usize),
     Up(usize),
     Down(usize),
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseCommandError;
+
 impl FromStr for Command {
-    type Err = ();
+    type Err = ParseCommandError;
 
     fn from_str(s: &str) -> Result<Command, Self::Err> {
         let Some((d, n)) = s.split_once(' ') else {
-            return Err(());
+            return Err(ParseCommandError);
         };
 
         let Ok(n) = n.parse() else {
-            return Err(());
+            return Err(ParseCommandError);
         };
 
         match d {
@@ -26,7 +29,7 @@ impl FromStr for Command {
             "R" => Ok(Command::Right(n)),
             "U" => Ok(Command::Up(n)),
             "D" => Ok(Command::Down(n)),
-            _ => Err(()),
+            _ => Err(ParseCommandError),
         }
     }
 }
@@ -53,7 +56,7 @@ impl Command {
 }
 
 // Return the number of positions tail visited at least once.
-fn num_tail_positions(s: &str, n: usize) -> usize {
+fn num_tail_positions(commands: &[Command], n: usize) -> usize {
     let mut knots = vec![(0, 0); n];
     let mut tail_positions: HashSet<(i64, i64)> = HashSet::new();
     tail_positions.insert(*knots.last().unwrap());
@@ -71,22 +74,25 @@ fn num_tail_positions(s: &str, n: usize) -> usize {
         tail_positions.insert(*knots.last().unwrap());
     };
 
-    let lines: Vec<&str> = s.trim().split('\n').collect();
-    for line in lines {
-        let command: Command = line.parse().unwrap();
+    for command in commands {
         command.execute(&mut update_head);
     }
     tail_positions.len()
 }
 
+/// Parse the motion on each line of the puzzle input.
+pub fn parse(s: &str) -> Result<Vec<Command>, ParseCommandError> {
+    s.trim().split('\n').map(str::parse).collect()
+}
+
 // Return the number of positions tail visited at least once.
-pub fn num_tail_positions_p1(s: &str) -> usize {
-    num_tail_positions(s, 2)
+pub fn part1(commands: &[Command]) -> usize {
+    num_tail_positions(commands, 2)
 }
 
 // Return the number of positions tail visited at least once.
-pub fn num_tail_positions_p2(s: &str) -> usize {
-    num_tail_positions(s, 10)
+pub fn part2(commands: &[Command]) -> usize {
+    num_tail_positions(commands, 10)
 }
 
 #[cfg(test)]
@@ -94,7 +100,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_num_tail_positions_p1() {
+    fn test_part1() {
         let input = "R 4
 U 4
 L 3
@@ -103,11 +109,11 @@ R 4
 D 1
 L 5
 R 2";
-        assert_eq!(num_tail_positions_p1(input), 13)
+        assert_eq!(part1(&parse(input).unwrap()), 13)
     }
 
     #[test]
-    fn test_num_tail_positions_p2() {
+    fn test_part2() {
         let input = "R 5
 U 8
 L 8
@@ -116,6 +122,6 @@ R 17
 D 10
 L 25
 U 20";
-        assert_eq!(num_tail_positions_p2(input), 36)
+        assert_eq!(part2(&parse(input).unwrap()), 36)
     }
 }
@@ -1,37 +1,44 @@
 use std::str::FromStr;
 
-#[derive(Debug)]
-enum Instruction {
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Instruction {
     Noop,
     AddX(i64),
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseInstructionError;
+
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = ParseInstructionError;
 
     fn from_str(s: &str) -> Result<Instruction, Self::Err> {
         let v: Vec<&str> = s.split(' ').collect();
 
         match v[0] {
             "noop" => Ok(Instruction::Noop),
-            "addx" => {
+            "addx" if v.len() == 2 => {
                 let Ok(n) = v[1].parse() else {
-                            return Err(());
-                        };
+                    return Err(ParseInstructionError);
+                };
 
                 Ok(Instruction::AddX(n))
             }
-            _ => Err(()),
+            _ => Err(ParseInstructionError),
         }
     }
 }
 
+/// Parse the program.
+pub fn parse(s: &str) -> Result<Vec<Instruction>, ParseInstructionError> {
+    s.trim().split('\n').map(str::parse).collect()
+}
+
 // Return register values after executing the given program.
-fn register_values(s: &str) -> Vec<i64> {
-    let instructions: Vec<Instruction> = s.trim().split('\n').map(|s| s.parse().unwrap()).collect();
+fn register_values(instructions: &[Instruction]) -> Vec<i64> {
     let mut x = 1;
     let mut values: Vec<i64> = vec![0];
-    for instruction in &instructions {
+    for instruction in instructions {
         match instruction {
             Instruction::Noop => {
                 values.push(x);
@@ -47,8 +54,8 @@ fn register_values(s: &str) -> Vec<i64> {
 }
 
 // Compute the sum of signal strengths for the given program.
-pub fn sum_of_signal_strengths(s: &str) -> i64 {
-    register_values(s)
+pub fn part1(instructions: &[Instruction]) -> i64 {
+    register_values(instructions)
         .iter()
         .enumerate()
         .skip(20)
@@ -57,8 +64,8 @@ pub fn sum_of_signal_strengths(s: &str) -> i64 {
 }
 
 // Render image on CRT based on the given program.
-pub fn render_image(s: &str) -> String {
-    let values = register_values(s);
+pub fn part2(instructions: &[Instruction]) -> String {
+    let values = register_values(instructions);
     const CRT_WIDTH: usize = 40;
     let crt_height = (values.len() - 1) / CRT_WIDTH;
     let mut screen = vec![vec!['#'; CRT_WIDTH]; crt_height];
@@ -86,7 +93,7 @@ mod tests {
         let input = "noop
 addx 3
 addx -5";
-        assert_eq!(sum_of_signal_strengths(input), 0);
+        assert_eq!(part1(&parse(input).unwrap()), 0);
     }
 
     const INPUT: &str = "addx 15
@@ -237,18 +244,18 @@ noop
 noop";
 
     #[test]
-    fn test_sum_of_signal_strengths() {
-        assert_eq!(sum_of_signal_strengths(INPUT), 13140)
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 13140)
     }
 
     #[test]
-    fn test_render_image() {
+    fn test_part2() {
         let output = "##..##..##..##..##..##..##..##..##..##..
 ###...###...###...###...###...###...###.
 ####....####....####....####....####....
 #####.....#####.....#####.....#####.....
 ######......######......######......####
 #######.......#######.......#######.....";
-        assert_eq!(render_image(INPUT), output)
+        assert_eq!(part2(&parse(INPUT).unwrap()), output)
     }
 }
@@ -1,16 +1,20 @@
+use crate::progress::{NoProgress, Progress};
 use std::collections::VecDeque;
 use std::str::FromStr;
 
+#[derive(Debug, Clone)]
 enum Operand {
     Old,
     Value(i64),
 }
 
+#[derive(Debug, Clone)]
 enum Operator {
     Add(Operand),
     Multiply(Operand),
 }
 
+#[derive(Debug, Clone)]
 pub struct Monkey {
     items: VecDeque<i64>,
     operation: Operator,
@@ -20,45 +24,45 @@ pub struct Monkey {
     num_inspections: usize,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseMonkeyError;
+
 impl FromStr for Monkey {
-    type Err = ();
+    type Err = ParseMonkeyError;
 
     fn from_str(s: &str) -> Result<Monkey, Self::Err> {
         let lines: Vec<&str> = s.trim().lines().skip(1).collect();
-
-        let items_str = lines[0].trim().strip_prefix("Starting items: ").unwrap();
-        let items: Vec<i64> = items_str.split(", ").map(|i| i.parse().unwrap()).collect();
-
-        let expr_str = lines[1].trim().strip_prefix("Operation: new = ").unwrap();
-        let (op_str, val_str) = expr_str.rsplit_once(' ').unwrap();
-        let operation = match val_str {
-            "old" => match op_str {
-                "old +" => Operator::Add(Operand::Old),
-                "old *" => Operator::Multiply(Operand::Old),
-                _ => unreachable!(),
-            },
-            v => match op_str {
-                "old +" => Operator::Add(Operand::Value(v.parse().unwrap())),
-                "old *" => Operator::Multiply(Operand::Value(v.parse().unwrap())),
-                _ => unreachable!(),
-            },
+        if lines.len() != 5 {
+            return Err(ParseMonkeyError);
+        }
+        let field =
+            |i: usize, prefix: &str| lines[i].trim().strip_prefix(prefix).ok_or(ParseMonkeyError);
+        let number = |s: &str| s.parse().map_err(|_| ParseMonkeyError);
+
+        let items_str = field(0, "Starting items: ")?;
+        let items: Vec<i64> = items_str
+            .split(", ")
+            .map(number)
+            .collect::<Result<_, _>>()?;
+
+        let expr_str = field(1, "Operation: new = ")?;
+        let (op_str, val_str) = expr_str.rsplit_once(' ').ok_or(ParseMonkeyError)?;
+        let operand = match val_str {
+            "old" => Operand::Old,
+            v => Operand::Value(number(v)?),
+        };
+        let operation = match op_str {
+            "old +" => Operator::Add(operand),
+            "old *" => Operator::Multiply(operand),
+            _ => return Err(ParseMonkeyError),
         };
 
-        let div_str = lines[2].trim().strip_prefix("Test: divisible by ").unwrap();
-        let test_div_by: i64 = div_str.parse().unwrap();
-
-        let on_true = lines[3]
-            .trim()
-            .strip_prefix("If true: throw to monkey ")
-            .unwrap()
-            .parse()
-            .unwrap();
-        let on_false = lines[4]
-            .trim()
-            .strip_prefix("If false: throw to monkey ")
-            .unwrap()
-            .parse()
-            .unwrap();
+        let test_div_by = number(field(2, "Test: divisible by ")?)?;
+        if test_div_by <= 0 {
+            return Err(ParseMonkeyError);
+        }
+        let on_true = number(field(3, "If true: throw to monkey ")?)? as usize;
+        let on_false = number(field(4, "If false: throw to monkey ")?)? as usize;
 
         Ok(Monkey {
             items: VecDeque::from_iter(items),
@@ -101,32 +105,66 @@ impl Monkey {
     }
 }
 
-pub fn monkey_business_level(s: &str, rounds: usize) -> usize {
-    let mut monkeys: Vec<Monkey> = s.trim().split("\n\n").map(|s| s.parse().unwrap()).collect();
+/// Parse the notes on each monkey.
+pub fn parse(s: &str) -> Result<Vec<Monkey>, ParseMonkeyError> {
+    let monkeys: Vec<Monkey> = s
+        .trim()
+        .split("\n\n")
+        .map(str::parse)
+        .collect::<Result<_, _>>()?;
+    if monkeys
+        .iter()
+        .any(|m| m.on_true >= monkeys.len() || m.on_false >= monkeys.len())
+    {
+        return Err(ParseMonkeyError);
+    }
+    Ok(monkeys)
+}
+
+/// Return the monkey business after the given number of rounds. Worry levels
+/// are divided by three after each inspection unless `relief` is off, in which
+/// case they are only kept manageable. Progress is reported after each round.
+pub fn monkey_business_level(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relief: bool,
+    progress: &dyn Progress,
+) -> usize {
+    let mut monkeys = monkeys.to_vec();
     let factor: i64 = monkeys.iter().map(|m| m.test_div_by).product();
-    for _ in 0..rounds {
+    for round in 0..rounds {
         for i in 0..monkeys.len() {
-            let moves = if rounds > 20 {
-                monkeys[i].turn(|worry| worry % factor)
-            } else {
+            let moves = if relief {
                 monkeys[i].turn(|worry| worry / 3)
+            } else {
+                monkeys[i].turn(|worry| worry % factor)
             };
             for (to, item) in moves {
                 monkeys[to].items.push_back(item);
             }
         }
+        let inspections = monkeys.iter().map(|m| m.num_inspections).sum();
+        progress.update(
+            "round",
+            round + 1,
+            Some(rounds),
+            ("inspections", inspections),
+        );
     }
     let mut inspections: Vec<usize> = monkeys.iter().map(|m| m.num_inspections).collect();
     inspections.sort_by(|a, b| b.cmp(a));
     inspections[0] * inspections[1]
 }
 
-pub fn monkey_business_level_p1(s: &str) -> usize {
-    monkey_business_level(s, 20)
+pub const ROUNDS_P1: usize = 20;
+pub const ROUNDS_P2: usize = 10000;
+
+pub fn part1(monkeys: &[Monkey]) -> usize {
+    monkey_business_level(monkeys, ROUNDS_P1, true, &NoProgress)
 }
 
-pub fn monkey_business_level_p2(s: &str) -> usize {
-    monkey_business_level(s, 10000)
+pub fn part2(monkeys: &[Monkey]) -> usize {
+    monkey_business_level(monkeys, ROUNDS_P2, false, &NoProgress)
 }
 
 #[cfg(test)]
@@ -175,12 +213,12 @@ mod tests {
             If false: throw to monkey 1";
 
     #[test]
-    fn test_monkey_business_level_p1() {
-        assert_eq!(monkey_business_level_p1(INPUT), 10605)
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 10605)
     }
 
     #[test]
-    fn test_monkey_business_level_p2() {
-        assert_eq!(monkey_business_level_p2(INPUT), 2713310158)
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 2713310158)
     }
 }
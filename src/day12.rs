@@ -1,33 +1,43 @@
 extern crate pathfinding;
 
 use pathfinding::prelude::bfs;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::str::FromStr;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 struct Position(usize, usize);
 
-#[derive(Debug)]
-struct HeightMap {
+#[derive(Debug, Clone)]
+pub struct HeightMap {
     grid: Vec<Vec<char>>,
     start: Position,
     end: Position,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseHeightMapError;
+
 impl FromStr for HeightMap {
-    type Err = ();
+    type Err = ParseHeightMapError;
 
     fn from_str(s: &str) -> Result<HeightMap, Self::Err> {
         let mut grid: Vec<Vec<char>> = s.trim().split('\n').map(|s| s.chars().collect()).collect();
-        let (mut start, mut end) = (Position(0, 0), Position(0, 0));
+        let (mut start, mut end) = (None, None);
         for (i, row) in grid.iter().enumerate() {
             for (j, &ch) in row.iter().enumerate() {
                 if ch == 'S' {
-                    start = Position(i, j);
+                    start = Some(Position(i, j));
                 } else if ch == 'E' {
-                    end = Position(i, j);
+                    end = Some(Position(i, j));
+                } else if !ch.is_ascii_lowercase() {
+                    return Err(ParseHeightMapError);
                 }
             }
         }
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(ParseHeightMapError);
+        };
         grid[start.0][start.1] = 'a';
         grid[end.0][end.1] = 'z';
         Ok(HeightMap { grid, start, end })
@@ -67,33 +77,37 @@ impl HeightMap {
     fn shortest_path(&self, start: &Position) -> Option<Vec<Position>> {
         bfs(start, |p| self.neighbors(p), |&p| p == self.end)
     }
+
+    // Return every position at the lowest elevation.
+    fn lowest_positions(&self) -> Vec<Position> {
+        let mut starts: Vec<Position> = Vec::new();
+        for (i, row) in self.grid.iter().enumerate() {
+            for (j, &ch) in row.iter().enumerate() {
+                if ch == 'a' {
+                    starts.push(Position(i, j));
+                }
+            }
+        }
+        starts
+    }
 }
 
-pub fn num_steps_to_target_p1(s: &str) -> usize {
-    let Ok(map) = s.parse::<HeightMap>() else {
-        unreachable!();
-    };
+/// Parse the heightmap.
+pub fn parse(s: &str) -> Result<HeightMap, ParseHeightMapError> {
+    s.parse()
+}
+
+pub fn part1(map: &HeightMap) -> usize {
     let Some(path) = map.shortest_path(&map.start) else {
         unreachable!();
     };
     path.len() - 1
 }
 
-pub fn num_steps_to_target_p2(s: &str) -> usize {
-    let Ok(map) = s.parse::<HeightMap>() else {
-        unreachable!();
-    };
-
-    let mut starts: Vec<Position> = Vec::new();
-    for (i, row) in map.grid.iter().enumerate() {
-        for (j, &ch) in row.iter().enumerate() {
-            if ch == 'a' {
-                starts.push(Position(i, j));
-            }
-        }
-    }
-
-    starts
+/// Search from one lowest position after the other for the fewest steps to the
+/// best signal.
+pub fn part2_sequential(map: &HeightMap) -> usize {
+    map.lowest_positions()
         .iter()
         .filter_map(|start| map.shortest_path(start))
         .map(|path| path.len() - 1)
@@ -101,6 +115,26 @@ pub fn num_steps_to_target_p2(s: &str) -> usize {
         .unwrap()
 }
 
+/// Search from every lowest position in parallel for the fewest steps to the
+/// best signal.
+#[cfg(feature = "parallel")]
+pub fn part2_parallel(map: &HeightMap) -> usize {
+    map.lowest_positions()
+        .par_iter()
+        .filter_map(|start| map.shortest_path(start))
+        .map(|path| path.len() - 1)
+        .min()
+        .unwrap()
+}
+
+pub fn part2(map: &HeightMap) -> usize {
+    #[cfg(feature = "parallel")]
+    let search = part2_parallel;
+    #[cfg(not(feature = "parallel"))]
+    let search = part2_sequential;
+    search(map)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,12 +146,15 @@ acctuvwj
 abdefghi";
 
     #[test]
-    fn test_num_steps_to_target_p1() {
-        assert_eq!(num_steps_to_target_p1(INPUT), 31);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 31);
     }
 
     #[test]
-    fn test_num_steps_to_target_p2() {
-        assert_eq!(num_steps_to_target_p2(INPUT), 29);
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 29);
+        #[cfg(feature = "parallel")]
+        assert_eq!(part2_parallel(&parse(INPUT).unwrap()), 29);
+        assert!(parse("abc\nabE").is_err());
     }
 }
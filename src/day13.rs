@@ -20,20 +20,42 @@ fn compare(left: Value, right: Value) -> Ordering {
     }
 }
 
-// Return the sum of indices of pairs who are in the right order.
-pub fn sum_right_indices(s: &str) -> usize {
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParsePacketError;
+
+// Return whether the value only holds lists and integers.
+fn is_packet(value: &Value) -> bool {
+    match value {
+        Value::Number(n) => n.is_i64(),
+        Value::Array(values) => values.iter().all(is_packet),
+        _ => false,
+    }
+}
+
+// Parse a single packet.
+fn parse_packet(s: &str) -> Result<Value, ParsePacketError> {
+    serde_json::from_str(s)
+        .ok()
+        .filter(is_packet)
+        .ok_or(ParsePacketError)
+}
+
+/// Parse the pairs of packets.
+pub fn parse(s: &str) -> Result<Vec<(Value, Value)>, ParsePacketError> {
     s.trim()
         .split("\n\n")
         .map(|s| {
-            let Some((left, right)) = s.split_once('\n') else {
-                unreachable!();
-        };
-            let (Ok(left), Ok(right)) = (serde_json::from_str(left),
-                                         serde_json::from_str(right)) else {
-                unreachable!();
-        };
-            compare(left, right)
+            let (left, right) = s.split_once('\n').ok_or(ParsePacketError)?;
+            Ok((parse_packet(left)?, parse_packet(right)?))
         })
+        .collect()
+}
+
+// Return the sum of indices of pairs who are in the right order.
+pub fn part1(pairs: &[(Value, Value)]) -> usize {
+    pairs
+        .iter()
+        .map(|(left, right)| compare(left.clone(), right.clone()))
         .enumerate()
         .fold(0, |acc, (i, ord)| {
             if ord == Ordering::Less {
@@ -45,12 +67,10 @@ pub fn sum_right_indices(s: &str) -> usize {
 }
 
 // Return the decoder key.
-pub fn decoder_key(s: &str) -> usize {
-    let mut packets: Vec<Value> = s
-        .trim()
-        .replace("\n\n", "\n")
-        .lines()
-        .map(|s| serde_json::from_str(s).unwrap())
+pub fn part2(pairs: &[(Value, Value)]) -> usize {
+    let mut packets: Vec<Value> = pairs
+        .iter()
+        .flat_map(|(left, right)| [left.clone(), right.clone()])
         .collect();
     let m1: Value = serde_json::from_str("[[2]]").unwrap();
     let m2: Value = serde_json::from_str("[[6]]").unwrap();
@@ -95,8 +115,8 @@ mod tests {
         ];
 
         for (i, (left, right, order)) in tests.iter().enumerate() {
-            let (Ok(left), Ok(right)) = (serde_json::from_str(left),
-                                         serde_json::from_str(right)) else {
+            let (Ok(left), Ok(right)) = (serde_json::from_str(left), serde_json::from_str(right))
+            else {
                 unreachable!();
             };
             assert_eq!(compare(left, right), order.clone(), "test {}", i);
@@ -128,12 +148,13 @@ mod tests {
 [1,[2,[3,[4,[5,6,0]]]],8,9]";
 
     #[test]
-    fn test_sum_right_indices() {
-        assert_eq!(sum_right_indices(INPUT), 13);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 13);
     }
 
     #[test]
-    fn test_decoder_key() {
-        assert_eq!(decoder_key(INPUT), 140);
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 140);
+        assert_eq!(parse("[1]\n{}"), Err(ParsePacketError));
     }
 }
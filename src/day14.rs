@@ -1,5 +1,6 @@
+use crate::visualize::Frame;
 use std::collections::HashMap;
-use std::num::ParseIntError;
+use std::iter;
 use std::str::FromStr;
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -8,14 +9,17 @@ struct Position {
     y: i64,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseScanError;
+
 impl FromStr for Position {
-    type Err = ParseIntError;
+    type Err = ParseScanError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y) = s.split_once(',').unwrap();
+        let (x, y) = s.split_once(',').ok_or(ParseScanError)?;
 
-        let x = x.parse::<i64>()?;
-        let y = y.parse::<i64>()?;
+        let x = x.parse::<i64>().map_err(|_| ParseScanError)?;
+        let y = y.parse::<i64>().map_err(|_| ParseScanError)?;
 
         Ok(Position { x, y })
     }
@@ -27,15 +31,16 @@ impl Position {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Material {
     Rock,
     // Air,
     Sand,
 }
 
-#[derive(Debug)]
-struct Ground {
+/// The scan of the cave, along with the sand that came to rest so far.
+#[derive(Debug, Clone)]
+pub struct Ground {
     source: Position,
     grid: HashMap<Position, Material>,
     deepest: i64,
@@ -54,11 +59,14 @@ impl Ground {
         }
     }
 
-    fn parse_path(&mut self, s: &str) {
-        let positions: Vec<&str> = s.trim().split(" -> ").collect();
+    fn parse_path(&mut self, s: &str) -> Result<(), ParseScanError> {
+        let positions: Vec<Position> = s
+            .trim()
+            .split(" -> ")
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
         for window in positions.windows(2) {
-            let p1 = window[0].parse::<Position>().unwrap();
-            let p2 = window[1].parse::<Position>().unwrap();
+            let (p1, p2) = (&window[0], &window[1]);
             if p1.x == p2.x {
                 for y in p1.y.min(p2.y)..=p1.y.max(p2.y) {
                     self.grid.insert(Position::new(p1.x, y), Material::Rock);
@@ -70,28 +78,58 @@ impl Ground {
             }
             self.deepest = self.deepest.max(p1.y).max(p2.y);
         }
+        Ok(())
     }
 
-    fn start_filling(&mut self) {
+    // Drop a unit of sand from the source and return where it comes to rest,
+    // or `None` if it falls into the abyss.
+    fn drop_unit(&mut self) -> Option<Position> {
         let mut position = self.source.clone();
-        loop {
-            match self.next_position(&position) {
-                None => {
-                    self.grid.insert(position.clone(), Material::Sand);
-                    self.num_sand_units += 1;
-                    if position == self.source {
-                        break;
-                    }
-                    position = self.source.clone();
-                }
-                Some(next) => {
-                    if !self.till_floor && next.y > self.deepest {
-                        break;
-                    }
-                    position = next
-                }
+        while let Some(next) = self.next_position(&position) {
+            if !self.till_floor && next.y > self.deepest {
+                return None;
             }
+            position = next;
         }
+        self.grid.insert(position.clone(), Material::Sand);
+        self.num_sand_units += 1;
+        Some(position)
+    }
+
+    fn is_full(&self) -> bool {
+        self.grid.contains_key(&self.source)
+    }
+
+    fn start_filling(&mut self) {
+        while !self.is_full() && self.drop_unit().is_some() {}
+    }
+
+    // Draw the scan from the source down to the deepest rock, or the floor.
+    fn rows(&self) -> Vec<String> {
+        let (min_x, max_x) = self
+            .grid
+            .keys()
+            .fold((self.source.x, self.source.x), |(min, max), p| {
+                (min.min(p.x), max.max(p.x))
+            });
+        let depth = if self.till_floor {
+            self.deepest + 2
+        } else {
+            self.deepest
+        };
+        (0..=depth)
+            .map(|y| {
+                (min_x - 1..=max_x + 1)
+                    .map(|x| match self.grid.get(&Position::new(x, y)) {
+                        _ if y == depth && self.till_floor => '#',
+                        Some(Material::Rock) => '#',
+                        Some(Material::Sand) => 'o',
+                        None if x == self.source.x && y == self.source.y => '+',
+                        None => '.',
+                    })
+                    .collect()
+            })
+            .collect()
     }
 
     fn next_position(&self, p: &Position) -> Option<Position> {
@@ -113,26 +151,60 @@ impl Ground {
     }
 }
 
-// Return number of resting sand units.
-pub fn num_resting_sand_units(s: &str, till_floor: bool) -> usize {
+/// Parse the scan of the rock paths.
+pub fn parse(s: &str) -> Result<Ground, ParseScanError> {
     let mut ground = Ground::new();
-    ground.till_floor = till_floor;
     let paths: Vec<&str> = s.trim().split('\n').collect();
     for path in paths {
-        ground.parse_path(path);
+        ground.parse_path(path)?;
     }
+    Ok(ground)
+}
+
+// Return the ground with sand falling till the floor or into the abyss.
+fn with_floor(ground: &Ground, till_floor: bool) -> Ground {
+    Ground {
+        till_floor,
+        ..ground.clone()
+    }
+}
+
+// Return number of resting sand units.
+pub fn num_resting_sand_units(ground: &Ground, till_floor: bool) -> usize {
+    let mut ground = with_floor(ground, till_floor);
     ground.start_filling();
     ground.num_sand_units
 }
 
+/// Return a frame of the scan before any sand falls and after each unit of
+/// sand comes to rest.
+pub fn frames(s: &str, till_floor: bool) -> impl Iterator<Item = Frame> {
+    let ground = parse(s).unwrap_or_else(|e| panic!("failed to parse scan: {:?}", e));
+    let mut ground = with_floor(&ground, till_floor);
+    let start = Frame {
+        caption: String::from("no sand yet"),
+        rows: ground.rows(),
+    };
+    iter::once(start).chain(iter::from_fn(move || {
+        if ground.is_full() {
+            return None;
+        }
+        let p = ground.drop_unit()?;
+        Some(Frame {
+            caption: format!("unit {} rests at {},{}", ground.num_sand_units, p.x, p.y),
+            rows: ground.rows(),
+        })
+    }))
+}
+
 // Return number of resting sand units according to part 1.
-pub fn num_resting_sand_units_p1(s: &str) -> usize {
-    num_resting_sand_units(s, false)
+pub fn part1(ground: &Ground) -> usize {
+    num_resting_sand_units(ground, false)
 }
 
 // Return number of resting sand units according to part 2.
-pub fn num_resting_sand_units_p2(s: &str) -> usize {
-    num_resting_sand_units(s, true)
+pub fn part2(ground: &Ground) -> usize {
+    num_resting_sand_units(ground, true)
 }
 
 #[cfg(test)]
@@ -143,12 +215,33 @@ mod tests {
 503,4 -> 502,4 -> 502,9 -> 494,9";
 
     #[test]
-    fn test_num_resting_sand_units() {
-        assert_eq!(num_resting_sand_units_p1(INPUT), 24);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 24);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 93);
     }
 
     #[test]
-    fn test_num_resting_sand_units_p2() {
-        assert_eq!(num_resting_sand_units_p2(INPUT), 93);
+    fn test_frames() {
+        let frames: Vec<Frame> = frames(INPUT, false).collect();
+        assert_eq!(frames.len(), 25);
+        assert_eq!(
+            frames[24].rows,
+            vec![
+                ".......+....",
+                "............",
+                ".......o....",
+                "......ooo...",
+                ".....#ooo##.",
+                "....o#ooo#..",
+                "...###ooo#..",
+                ".....oooo#..",
+                "..o.ooooo#..",
+                ".#########..",
+            ]
+        );
     }
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::fmt;
 use std::str::FromStr;
 
@@ -15,28 +17,17 @@ impl Position {
     fn manhattan_distance(&self, other: &Position) -> i64 {
         (self.x - other.x).abs() + (self.y - other.y).abs()
     }
-
-    fn neighbors_within(&self, distance: i64) -> Vec<Position> {
-        let mut neighbors = Vec::new();
-        for y in self.y - distance..=self.y + distance {
-            let offset = distance - (y - self.y).abs();
-            for x in self.x - offset..=self.x + offset {
-                neighbors.push(Position::new(x, y));
-            }
-        }
-        neighbors
-    }
 }
 
 #[derive(Clone, Debug)]
-struct ReportEntry {
+pub struct ReportEntry {
     sensor: Position,
     beacon: Position,
     distance: i64,
 }
 
-#[derive(Debug)]
-struct ParseReportEntryError;
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseReportEntryError;
 
 impl From<std::num::ParseIntError> for ParseReportEntryError {
     fn from(_: std::num::ParseIntError) -> ParseReportEntryError {
@@ -51,9 +42,12 @@ impl FromStr for ReportEntry {
         let (sensor, beacon) = s.split_once(':').ok_or(ParseReportEntryError)?;
         let (sx, sy) = sensor.split_once(',').ok_or(ParseReportEntryError)?;
         let (bx, by) = beacon.split_once(',').ok_or(ParseReportEntryError)?;
+        let number = |s: &str, skip: usize| -> Result<i64, ParseReportEntryError> {
+            Ok(s.get(skip..).ok_or(ParseReportEntryError)?.parse()?)
+        };
         Ok(ReportEntry::new(
-            Position::new(sx[12..].parse::<i64>()?, sy[3..].parse::<i64>()?),
-            Position::new(bx[24..].parse::<i64>()?, by[3..].parse::<i64>()?),
+            Position::new(number(sx, 12)?, number(sy, 3)?),
+            Position::new(number(bx, 24)?, number(by, 3)?),
         ))
     }
 }
@@ -76,16 +70,13 @@ impl ReportEntry {
     }
 }
 
+/// Parse the report of each sensor.
+pub fn parse(s: &str) -> Result<Vec<ReportEntry>, ParseReportEntryError> {
+    s.trim().lines().map(str::parse).collect()
+}
+
 // Return the number of empty positions in the given row.
-pub fn num_empty_positions(s: &str, row: i64) -> usize {
-    let report: Vec<ReportEntry> = s
-        .trim()
-        .lines()
-        .map(|s| {
-            s.parse()
-                .unwrap_or_else(|_| panic!("failed to parse entry: {}", s))
-        })
-        .collect();
+pub fn num_empty_positions(report: &[ReportEntry], row: i64) -> usize {
     let (min_x, max_x) = report
         .iter()
         .map(|entry| {
@@ -106,36 +97,64 @@ pub fn num_empty_positions(s: &str, row: i64) -> usize {
         .count()
 }
 
-// Return the tuning frequency of the distress beacon within given bound.
-pub fn distress_beacon_tuning_frequency(s: &str, bound: i64) -> i64 {
-    let report: Vec<ReportEntry> = s
-        .trim()
-        .lines()
-        .map(|s| {
-            s.parse()
-                .unwrap_or_else(|_| panic!("failed to parse entry: {}", s))
-        })
-        .collect();
+// Return the tuning frequency of the distress beacon if it lies within the
+// given bound just outside the range of the sensor of the entry.
+fn beacon_near(report: &[ReportEntry], entry: &ReportEntry, bound: i64) -> Option<i64> {
+    let (min_x, max_x) = (
+        0.max(entry.sensor.x - entry.distance - 1),
+        bound.min(entry.sensor.x),
+    );
+    let (min_y, max_y) = (entry.sensor.y, bound);
+    // Courtesy: u/Sh4d1.
+    (min_x..=max_x).zip(min_y..=max_y).find_map(|(x, y)| {
+        let position = Position::new(x, y);
+        report
+            .iter()
+            .all(|entry| !entry.is_in_range(&position))
+            .then_some(position.x * 4000000 + position.y)
+    })
+}
+
+/// Search around one sensor after the other for the distress beacon within the
+/// given bound, and return its tuning frequency.
+pub fn distress_beacon_tuning_frequency_sequential(report: &[ReportEntry], bound: i64) -> i64 {
     report
         .iter()
-        .find_map(|entry| {
-            let (min_x, max_x) = (
-                0.max(entry.sensor.x - entry.distance - 1),
-                bound.min(entry.sensor.x),
-            );
-            let (min_y, max_y) = (entry.sensor.y, bound);
-            // Courtesy: u/Sh4d1.
-            (min_x..=max_x).zip(min_y..=max_y).find_map(|(x, y)| {
-                let position = Position::new(x, y);
-                report
-                    .iter()
-                    .all(|entry| !entry.is_in_range(&position))
-                    .then_some(position.x * 4000000 + position.y)
-            })
-        })
+        .find_map(|entry| beacon_near(report, entry, bound))
         .unwrap()
 }
 
+/// Search around every sensor in parallel for the distress beacon within the
+/// given bound, and return its tuning frequency. The beacon found around the
+/// first sensor wins, as when searching sequentially.
+#[cfg(feature = "parallel")]
+pub fn distress_beacon_tuning_frequency_parallel(report: &[ReportEntry], bound: i64) -> i64 {
+    report
+        .par_iter()
+        .find_map_first(|entry| beacon_near(report, entry, bound))
+        .unwrap()
+}
+
+// Return the tuning frequency of the distress beacon within given bound.
+pub fn distress_beacon_tuning_frequency(report: &[ReportEntry], bound: i64) -> i64 {
+    #[cfg(feature = "parallel")]
+    let search = distress_beacon_tuning_frequency_parallel;
+    #[cfg(not(feature = "parallel"))]
+    let search = distress_beacon_tuning_frequency_sequential;
+    search(report, bound)
+}
+
+pub const ROW: i64 = 2000000;
+pub const BOUND: i64 = 4000000;
+
+pub fn part1(report: &[ReportEntry]) -> usize {
+    num_empty_positions(report, ROW)
+}
+
+pub fn part2(report: &[ReportEntry]) -> i64 {
+    distress_beacon_tuning_frequency(report, BOUND)
+}
+
 // For visualization only.
 #[derive(Clone, Debug)]
 enum Content {
@@ -155,108 +174,58 @@ impl fmt::Display for Content {
         })
     }
 }
-struct Map {
-    report: Vec<ReportEntry>,
-    contents: Vec<Vec<Content>>,
-    min_x: i64,
-    min_y: i64,
-}
+/// Draw the sensors, beacons and the positions they rule out, shrunk so that
+/// neither side exceeds `max_size` tiles. A tile stands for a square block of
+/// positions and shows a sensor or beacon if one is in the block, or else
+/// whether the centre of the block is ruled out.
+pub fn coverage(s: &str, max_size: usize) -> Vec<String> {
+    let report = parse(s).unwrap_or_else(|e| panic!("failed to parse report: {:?}", e));
+    let (min_x, min_y, max_x, max_y) = report.iter().fold(
+        (i64::MAX, i64::MAX, i64::MIN, i64::MIN),
+        |(min_x, min_y, max_x, max_y), entry| {
+            (
+                min_x.min(entry.sensor.x - entry.distance),
+                min_y.min(entry.sensor.y - entry.distance),
+                max_x.max(entry.sensor.x + entry.distance),
+                max_y.max(entry.sensor.y + entry.distance),
+            )
+        },
+    );
+    let span = (max_x - min_x).max(max_y - min_y) + 1;
+    let block = (span + max_size as i64 - 1) / max_size.max(1) as i64;
+    let (columns, rows) = ((max_x - min_x) / block + 1, (max_y - min_y) / block + 1);
 
-impl fmt::Debug for Map {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (i, row) in self.contents.iter().enumerate() {
-            if i > 0 {
-                f.write_str("\n")?;
-            }
-            for content in row {
-                f.write_str(&content.to_string())?;
+    let mut contents = vec![vec![Content::Unknown; columns as usize]; rows as usize];
+    for (r, row) in contents.iter_mut().enumerate() {
+        for (c, content) in row.iter_mut().enumerate() {
+            let centre = Position::new(
+                min_x + c as i64 * block + block / 2,
+                min_y + r as i64 * block + block / 2,
+            );
+            if report
+                .iter()
+                .any(|entry| entry.sensor.manhattan_distance(&centre) <= entry.distance)
+            {
+                *content = Content::Empty;
             }
         }
-        Ok(())
     }
-}
-
-impl Map {
-    pub fn from_report(report: Vec<ReportEntry>) -> Self {
-        let boundary = (i64::MAX, i64::MAX, i64::MIN, i64::MIN);
-        let boundary = report
-            .iter()
-            .fold(boundary, |(min_x, min_y, max_x, max_y), entry| {
-                (
-                    min_x.min(entry.sensor.x).min(entry.beacon.x),
-                    min_y.min(entry.sensor.y).min(entry.beacon.y),
-                    max_x.max(entry.sensor.x).max(entry.beacon.x),
-                    max_y.max(entry.sensor.y).max(entry.beacon.y),
-                )
-            });
-        let max_distance = report
-            .iter()
-            .map(|e| e.beacon.manhattan_distance(&e.sensor))
-            .max()
-            .unwrap();
-        let (min_x, min_y) = (boundary.0 - max_distance - 1, boundary.1 - max_distance - 1);
-        let (max_x, max_y) = (boundary.2 + max_distance + 1, boundary.3 + max_distance + 1);
-
-        let rows = (max_y - min_y) as usize;
-        let columns = (max_x - min_x) as usize;
-        let contents = vec![vec![Content::Unknown; columns]; rows];
-        let mut map = Map {
-            report,
-            contents,
-            min_x,
-            min_y,
+    for entry in &report {
+        let tile = |p: &Position| {
+            (
+                ((p.y - min_y) / block) as usize,
+                ((p.x - min_x) / block) as usize,
+            )
         };
-        map.scan();
-        map
-    }
-
-    pub fn index(&self, position: &Position) -> (usize, usize) {
-        let (column, row) = ((position.x - self.min_x), (position.y - self.min_y));
-        (row as usize, column as usize)
-    }
-
-    pub fn put(&mut self, position: &Position, content: Content) {
-        let (row, column) = self.index(position);
-        self.contents[row][column] = content;
+        let (r, c) = tile(&entry.beacon);
+        contents[r][c] = Content::Beacon;
+        let (r, c) = tile(&entry.sensor);
+        contents[r][c] = Content::Sensor;
     }
-
-    pub fn get(&self, position: &Position) -> Content {
-        let (row, column) = self.index(position);
-        self.contents[row][column].clone()
-    }
-
-    pub fn scan(&mut self) {
-        let entries = self.report.clone();
-        for entry in &entries {
-            self.put(&entry.sensor, Content::Sensor);
-            self.put(&entry.beacon, Content::Beacon);
-        }
-
-        for entry in &entries {
-            let distance = entry.sensor.manhattan_distance(&entry.beacon);
-            let neighbors = entry.sensor.neighbors_within(distance);
-            for neighbor in neighbors {
-                if let Content::Unknown = self.get(&neighbor) {
-                    self.put(&neighbor, Content::Empty);
-                }
-            }
-        }
-    }
-}
-
-// Visualize the given input.
-#[allow(dead_code)]
-fn visualize(s: &str) {
-    let report: Vec<ReportEntry> = s
-        .trim()
-        .lines()
-        .map(|s| {
-            s.parse()
-                .unwrap_or_else(|_| panic!("failed to parse entry: {}", s))
-        })
-        .collect();
-    let map = Map::from_report(report);
-    println!("{:?}", map);
+    contents
+        .iter()
+        .map(|row| row.iter().map(|content| content.to_string()).collect())
+        .collect()
 }
 
 #[cfg(test)]
@@ -280,11 +249,30 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
 
     #[test]
     fn test_num_empty_positions() {
-        assert_eq!(num_empty_positions(INPUT, 10), 26);
+        assert_eq!(num_empty_positions(&parse(INPUT).unwrap(), 10), 26);
     }
 
     #[test]
     fn test_distress_beacon_tuning_frequency() {
-        assert_eq!(distress_beacon_tuning_frequency(INPUT, 20), 56000011);
+        assert_eq!(
+            distress_beacon_tuning_frequency(&parse(INPUT).unwrap(), 20),
+            56000011
+        );
+        #[cfg(feature = "parallel")]
+        assert_eq!(
+            distress_beacon_tuning_frequency_parallel(&parse(INPUT).unwrap(), 20),
+            56000011
+        );
+    }
+
+    #[test]
+    fn test_coverage() {
+        let rows = coverage(INPUT, 100);
+        assert_eq!(rows.len(), 37);
+        // Row 10 of the example, as drawn in the puzzle description, lies 10 rows
+        // below the top of the coverage.
+        assert!(rows[10 + 10].contains("..####B######################.."));
+        let small = coverage(INPUT, 10);
+        assert!(small.len() <= 10 && small.iter().all(|row| row.len() <= 10));
     }
 }
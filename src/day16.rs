@@ -1,3 +1,6 @@
+use crate::cancel::{Cancelled, Token};
+use crate::event;
+use crate::trace::Level;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
@@ -10,8 +13,8 @@ struct ReportEntry {
     leads_to: Vec<String>,
 }
 
-#[derive(Debug)]
-struct ParseReportEntryError;
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseReportEntryError;
 
 impl From<std::num::ParseIntError> for ParseReportEntryError {
     fn from(_: std::num::ParseIntError) -> ParseReportEntryError {
@@ -45,7 +48,6 @@ impl FromStr for ReportEntry {
             .get(3)
             .ok_or(ParseReportEntryError)?
             .as_str()
-            .to_string()
             .split(", ")
             .map(String::from)
             .collect();
@@ -74,12 +76,16 @@ impl Valve {
     }
 }
 
-#[derive(Debug)]
-struct ValveTable {
+/// The valves and the tunnels between them.
+#[derive(Debug, Clone)]
+pub struct ValveTable {
     ids: HashMap<String, i64>,
     valves: HashMap<i64, Valve>,
 }
 
+// Number of states between two checks of the token.
+const CHECK_INTERVAL: usize = 4096;
+
 impl ValveTable {
     fn from_report_entries(entries: &[ReportEntry]) -> Self {
         let mut names: Vec<String> = entries.iter().map(|e| e.valve.clone()).collect();
@@ -103,23 +109,26 @@ impl ValveTable {
     // FIXME: This is still crazy slow. Took 175s on M1 Max. Optimize.
     // Irrespective, this won't cut it for part 2.
     // Courtesy: u/morgoth1145
-    fn max_released_pressure(&self) -> i64 {
-        const DURATION: i64 = 30;
+    /// Return the most pressure that can be released in `duration` minutes,
+    /// checking the token every few thousand states.
+    pub fn max_released_pressure(&self, duration: i64, token: &Token) -> Result<i64, Cancelled> {
         let mut states: HashSet<(i64, i64, i64)> = HashSet::new();
         states.insert((self.get_by_name(&String::from("AA")).id, 0, 0));
-        // let mut states = vec![(self.get_by_name(&String::from("AA")).id, 0, 0)];
         let mut best: HashMap<(i64, i64), i64> = HashMap::new();
-        for t in 1..=DURATION {
-            // println!("time: {}, num_states: {}", t, states.len());
-            // println!(
-            //     "time: {}, num_states: {}, states: {:?}",
-            //     t,
-            //     states.len(),
-            //     states
-            // );
+        // A state without tunnels to follow has no successor, so keep the most
+        // pressure of any state seen rather than of the last ones only.
+        let mut most = 0;
+        for t in 1..=duration {
+            event!(Level::Debug, "time: {}, num_states: {}", t, states.len());
+            event!(Level::Trace, "time: {}, states: {:?}", t, states);
             let mut next_states: HashSet<(i64, i64, i64)> = HashSet::new();
-            // let mut next_states: Vec<(i64, i64, i64)> = Vec::new();
-            for (current, opened, pressure) in &states {
+            for (i, (current, opened, pressure)) in states.iter().enumerate() {
+                // A single minute can take seconds with hundreds of thousands
+                // of states.
+                if i % CHECK_INTERVAL == 0 {
+                    token.check()?;
+                }
+                most = most.max(*pressure);
                 if let Some(best_pressure) = best.get(&(*current, *pressure)) {
                     if best_pressure > pressure {
                         continue;
@@ -132,44 +141,51 @@ impl ValveTable {
                     next_states.insert((
                         *current,
                         current | opened,
-                        pressure + valve.flow_rate * (DURATION - t),
+                        pressure + valve.flow_rate * (duration - t),
                     ));
-                    // next_states.push((
-                    //     *current,
-                    //     current | opened,
-                    //     pressure + valve.flow_rate * (DURATION - t),
-                    // ));
                 }
                 for next in &valve.leads_to {
                     next_states.insert((*next, *opened, *pressure));
-                    // next_states.push((*next, *opened, *pressure));
                 }
             }
             states = next_states;
         }
-        *states
+        Ok(states
             .iter()
-            .map(|(_, _, pressure)| pressure)
-            .max()
-            .unwrap()
+            .map(|(_, _, pressure)| *pressure)
+            .fold(most, i64::max))
     }
 }
 
-pub fn max_released_pressure(s: &str) -> i64 {
-    let entries: Vec<ReportEntry> = s
-        .trim()
-        .lines()
-        .map(|s| {
-            s.parse()
-                .unwrap_or_else(|_| panic!("failed to parse entry: {}", s))
-        })
-        .collect();
-    ValveTable::from_report_entries(&entries).max_released_pressure()
+/// Parse the report of each valve. Every tunnel must lead to a reported
+/// valve, and the valve `AA` to start from must be among them.
+pub fn parse(s: &str) -> Result<ValveTable, ParseReportEntryError> {
+    let entries: Vec<ReportEntry> = s.trim().lines().map(str::parse).collect::<Result<_, _>>()?;
+    let reported = |name: &String| entries.iter().any(|e| e.valve == *name);
+    // Each valve is one bit of the i64 masks of opened valves, leaving the
+    // sign bit unused.
+    if entries.len() >= 63
+        || !reported(&String::from("AA"))
+        || !entries.iter().all(|e| e.leads_to.iter().all(reported))
+    {
+        return Err(ParseReportEntryError);
+    }
+    Ok(ValveTable::from_report_entries(&entries))
+}
+
+pub const MINUTES: i64 = 30;
+
+pub fn part1(table: &ValveTable) -> i64 {
+    table
+        .max_released_pressure(MINUTES, &Token::default())
+        .unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use std::collections::VecDeque;
 
     static INPUT: &str = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
 Valve BB has flow rate=13; tunnels lead to valves CC, AA
@@ -183,7 +199,151 @@ Valve II has flow rate=0; tunnels lead to valves AA, JJ
 Valve JJ has flow rate=21; tunnel leads to valve II";
 
     #[test]
-    fn test_max_released_pressure() {
-        assert_eq!(max_released_pressure(INPUT), 1651);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 1651);
+        assert!(parse("Valve AA has flow rate=0; tunnel leads to valve BB").is_err());
+        let token = Token::default();
+        token.cancel();
+        assert_eq!(
+            parse(INPUT).unwrap().max_released_pressure(MINUTES, &token),
+            Err(Cancelled)
+        );
+    }
+
+    #[test]
+    fn test_no_tunnels() {
+        for (flow_rate, pressure) in [(0, 0), (5, 145)] {
+            let entry = ReportEntry {
+                valve: String::from("AA"),
+                flow_rate,
+                leads_to: Vec::new(),
+            };
+            let table = ValveTable::from_report_entries(&[entry]);
+            assert_eq!(
+                table.max_released_pressure(MINUTES, &Token::default()),
+                Ok(pressure)
+            );
+        }
+    }
+
+    fn valve_name(i: usize) -> String {
+        if i == 0 {
+            String::from("AA")
+        } else {
+            format!(
+                "{}{}",
+                (b'B' + (i / 26) as u8) as char,
+                (b'A' + (i % 26) as u8) as char
+            )
+        }
+    }
+
+    // Generate connected valve graphs of up to 7 valves, about half of which
+    // have a non-zero flow rate.
+    fn valve_graph() -> impl Strategy<Value = String> {
+        (2usize..=7)
+            .prop_flat_map(|n| {
+                (
+                    prop::collection::vec(any::<prop::sample::Index>(), n),
+                    prop::collection::vec(prop_oneof![Just(0i64), 1i64..25], n),
+                    prop::collection::vec((0..n, 0..n), 0..n),
+                )
+            })
+            .prop_map(|(parents, flow_rates, extra)| {
+                let n = flow_rates.len();
+                let mut tunnels: Vec<Vec<usize>> = vec![Vec::new(); n];
+                let mut connect = |a: usize, b: usize| {
+                    if a != b && !tunnels[a].contains(&b) {
+                        tunnels[a].push(b);
+                        tunnels[b].push(a);
+                    }
+                };
+                for (i, parent) in parents.iter().enumerate().skip(1) {
+                    connect(i, parent.index(i));
+                }
+                for (a, b) in extra {
+                    connect(a, b);
+                }
+                (0..n)
+                    .map(|i| {
+                        let leads_to: Vec<String> =
+                            tunnels[i].iter().map(|&j| valve_name(j)).collect();
+                        let plural = if leads_to.len() == 1 { "" } else { "s" };
+                        format!(
+                            "Valve {} has flow rate={}; tunnel{} lead{} to valve{} {}",
+                            valve_name(i),
+                            flow_rates[i],
+                            plural,
+                            if plural.is_empty() { "s" } else { "" },
+                            plural,
+                            leads_to.join(", ")
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+    }
+
+    // Find the best order to open valves by trying every order, moving along
+    // shortest paths between valves with a non-zero flow rate.
+    fn exhaustive_max_released_pressure(s: &str) -> i64 {
+        let entries: Vec<ReportEntry> = s.lines().map(|l| l.parse().unwrap()).collect();
+        let index: HashMap<&str, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.valve.as_str(), i))
+            .collect();
+        let distances: Vec<Vec<i64>> = (0..entries.len())
+            .map(|from| {
+                let mut distance = vec![i64::MAX; entries.len()];
+                distance[from] = 0;
+                let mut queue = VecDeque::from([from]);
+                while let Some(v) = queue.pop_front() {
+                    for next in &entries[v].leads_to {
+                        let next = index[next.as_str()];
+                        if distance[next] == i64::MAX {
+                            distance[next] = distance[v] + 1;
+                            queue.push_back(next);
+                        }
+                    }
+                }
+                distance
+            })
+            .collect();
+
+        fn search(
+            entries: &[ReportEntry],
+            distances: &[Vec<i64>],
+            at: usize,
+            time_left: i64,
+            opened: &mut Vec<bool>,
+        ) -> i64 {
+            let mut best = 0;
+            for next in 0..entries.len() {
+                let remaining = time_left - distances[at][next] - 1;
+                if opened[next] || entries[next].flow_rate == 0 || remaining <= 0 {
+                    continue;
+                }
+                opened[next] = true;
+                best = best.max(
+                    entries[next].flow_rate * remaining
+                        + search(entries, distances, next, remaining, opened),
+                );
+                opened[next] = false;
+            }
+            best
+        }
+
+        let mut opened = vec![false; entries.len()];
+        search(&entries, &distances, index["AA"], 30, &mut opened)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn prop_max_released_pressure(s in valve_graph()) {
+            prop_assert_eq!(part1(&parse(&s).unwrap()), exhaustive_max_released_pressure(&s));
+        }
     }
 }
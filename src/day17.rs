@@ -1,11 +1,20 @@
+use crate::cancel::{Cancelled, Token};
+use crate::event;
+use crate::trace::Level;
+use crate::visualize::Frame;
 use std::fmt;
+use std::iter;
 
-const NUM_ROCKS: usize = 2022;
+pub const NUM_ROCKS: usize = 2022;
 const NUM_ROCK_KINDS: usize = 5;
 const TOTAL_ROCK_KINDS_HEIGHT: usize = 13;
 const HEIGHT: usize = TOTAL_ROCK_KINDS_HEIGHT / NUM_ROCK_KINDS * NUM_ROCKS;
-const WIDTH: usize = 7;
+pub const WIDTH: usize = 7;
+/// The narrowest chamber a horizontal bar fits in when it appears.
+pub const MIN_WIDTH: usize = 6;
 const CUSHION: usize = 7;
+// Rows of the chamber drawn below the top of the falling rock.
+const TRACE_HEIGHT: usize = 40;
 
 #[derive(Debug, Clone)]
 struct Position {
@@ -116,8 +125,9 @@ impl Rock {
     }
 }
 
-#[derive(Debug)]
-enum Direction {
+/// The direction a jet of gas pushes a rock in.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Direction {
     Right,
     Left,
 }
@@ -160,7 +170,7 @@ impl fmt::Debug for Chamber {
             }
             write!(f, "| {}", i)?;
         }
-        f.write_str("\n+-------+\n")?;
+        write!(f, "\n{}\n", self.floor())?;
         Ok(())
     }
 }
@@ -177,19 +187,42 @@ impl Chamber {
         chamber
     }
 
-    #[allow(dead_code)]
-    fn trace_falling_rock(&mut self, rock: &Rock, position: &Position) {
-        for p in rock.shape() {
-            let x = position.x + p.x;
-            let y = position.y - p.y;
-            self.states[y][x] = CellState::FallingRock;
-        }
-        println!("{:?}", self);
-        for p in rock.shape() {
-            let x = position.x + p.x;
-            let y = position.y - p.y;
-            self.states[y][x] = CellState::Empty;
+    fn width(&self) -> usize {
+        self.states[0].len()
+    }
+
+    fn floor(&self) -> String {
+        format!("+{}+", "-".repeat(self.width()))
+    }
+
+    // Draw the top of the chamber along with the falling rock, if any.
+    fn draw(&self, falling: Option<(&Rock, &Position)>) -> Vec<String> {
+        let cells: Vec<(usize, usize)> = falling.map_or_else(Vec::new, |(rock, position)| {
+            rock.shape()
+                .iter()
+                .map(|p| (position.x + p.x, position.y - p.y))
+                .collect()
+        });
+        let top = falling.map_or(0, |(_, p)| p.y).max(self.first_rock_at);
+        let mut rows: Vec<String> = (top.saturating_sub(TRACE_HEIGHT).max(1)..=top)
+            .rev()
+            .map(|y| {
+                let row: String = (0..self.width())
+                    .map(|x| {
+                        if cells.contains(&(x, y)) {
+                            CellState::FallingRock.to_string()
+                        } else {
+                            self.states[y][x].to_string()
+                        }
+                    })
+                    .collect();
+                format!("|{}| {}", row, y)
+            })
+            .collect();
+        if top <= TRACE_HEIGHT {
+            rows.push(self.floor());
         }
+        rows
     }
 
     fn can_move_down(&self, rock: &Rock, position: &Position) -> bool {
@@ -217,23 +250,35 @@ impl Chamber {
     }
 
     fn execute_move(&mut self, mv: &Direction, rock: &Rock, position: &Position) -> Position {
-        // println!("Executing {:?} at position {:?}", mv, position);
+        event!(
+            Level::Trace,
+            "Executing {:?} at position {:?}",
+            mv,
+            position
+        );
         match mv {
             Direction::Left => {
                 if position.x == 0 || !self.can_move_left(rock, position) {
-                    // println!("Jet of gas pushes rock left, but nothing happens:");
+                    event!(
+                        Level::Trace,
+                        "Jet of gas pushes rock left, but nothing happens"
+                    );
                     position.clone()
                 } else {
-                    // println!("Jet of gas pushes rock left:");
+                    event!(Level::Trace, "Jet of gas pushes rock left");
                     Position::new(position.x - 1, position.y)
                 }
             }
             Direction::Right => {
-                if position.x + rock.width() == WIDTH || !self.can_move_right(rock, position) {
-                    // println!("Jet of gas pushes rock right, but nothing happens:");
+                if position.x + rock.width() == self.width() || !self.can_move_right(rock, position)
+                {
+                    event!(
+                        Level::Trace,
+                        "Jet of gas pushes rock right, but nothing happens"
+                    );
                     position.clone()
                 } else {
-                    // println!("Jet of gas pushes rock right:");
+                    event!(Level::Trace, "Jet of gas pushes rock right");
                     Position::new(position.x + 1, position.y)
                 }
             }
@@ -245,7 +290,7 @@ impl Chamber {
         if available < CUSHION {
             let needed = CUSHION - available;
             self.states
-                .append(&mut vec![vec![CellState::Empty; WIDTH]; needed]);
+                .append(&mut vec![vec![CellState::Empty; self.width()]; needed]);
             self.last_row += needed;
         }
     }
@@ -258,12 +303,19 @@ impl Chamber {
             self.first_rock_at = self.first_rock_at.max(position.y - p.y)
         }
         self.allocate_rows_if_needed();
-        // println!("Rock falls 1 unit, causing it to come to rest:");
-        // println!("{:?}", self);
+        event!(
+            Level::Trace,
+            "Rock falls 1 unit, causing it to come to rest"
+        );
     }
 
-    fn simulate(&mut self, moves: &[Direction]) -> Result<usize, ()> {
-        let rocks = vec![
+    fn simulate(
+        &mut self,
+        moves: &[Direction],
+        num_rocks: usize,
+        token: &Token,
+    ) -> Result<usize, Cancelled> {
+        let rocks = [
             Rock::HorizontalBar,
             Rock::Plus,
             Rock::InvertedL,
@@ -271,39 +323,132 @@ impl Chamber {
             Rock::Square,
         ];
         let mut moves = moves.iter().cycle();
-        for rock in rocks.iter().cycle().take(NUM_ROCKS) {
+        for rock in rocks.iter().cycle().take(num_rocks) {
+            token.check()?;
             let mut position = Position::new(2, self.first_rock_at + 3 + rock.height());
-            // println!("A new rock begins falling: {:?}", rock);
+            event!(Level::Debug, "A new rock begins falling: {:?}", rock);
             loop {
-                // self.trace_falling_rock(rock, &position);
                 position = self.execute_move(moves.next().unwrap(), rock, &position);
-                // self.trace_falling_rock(rock, &position);
+                event!(
+                    Level::Trace,
+                    "\n{}",
+                    self.draw(Some((rock, &position))).join("\n")
+                );
 
                 if !self.can_move_down(rock, &position) {
                     self.rest_rock(rock, &position);
                     break;
                 }
                 position.y -= 1;
-                // println!("Rock falls 1 unit:");
+                event!(Level::Trace, "Rock falls 1 unit");
             }
         }
-        // println!("{:?}", self);
+        event!(Level::Trace, "{:?}", self);
         Ok(self.first_rock_at)
     }
 }
 
-pub fn tower_height(s: &str) -> usize {
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseJetsError;
+
+/// Parse the pattern of the jets of gas.
+pub fn parse(s: &str) -> Result<Vec<Direction>, ParseJetsError> {
     let moves: Vec<Direction> = s
         .trim()
         .chars()
         .map(|c| match c {
-            '<' => Direction::Left,
-            '>' => Direction::Right,
-            _ => unreachable!(),
+            '<' => Ok(Direction::Left),
+            '>' => Ok(Direction::Right),
+            _ => Err(ParseJetsError),
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
+    if moves.is_empty() {
+        return Err(ParseJetsError);
+    }
+    Ok(moves)
+}
+
+/// Return the height of the tower after `num_rocks` rocks came to rest in a
+/// chamber of the given width, which must be at least `MIN_WIDTH`. The token
+/// is checked before each rock.
+pub fn tower_height(
+    moves: &[Direction],
+    num_rocks: usize,
+    width: usize,
+    token: &Token,
+) -> Result<usize, Cancelled> {
+    let mut chamber = Chamber::new(HEIGHT, width);
+    chamber.simulate(moves, num_rocks, token)
+}
+
+pub fn part1(moves: &[Direction]) -> usize {
+    tower_height(moves, NUM_ROCKS, WIDTH, &Token::default()).unwrap()
+}
+
+/// Return a frame for each rock appearing, each push by a jet of gas and each
+/// time a rock falls or comes to rest.
+pub fn frames(s: &str) -> impl Iterator<Item = Frame> {
+    let rocks = [
+        Rock::HorizontalBar,
+        Rock::Plus,
+        Rock::InvertedL,
+        Rock::VerticalBar,
+        Rock::Square,
+    ];
+    let moves = parse(s).unwrap_or_else(|e| panic!("failed to parse jets: {:?}", e));
     let mut chamber = Chamber::new(HEIGHT, WIDTH);
-    chamber.simulate(&moves).unwrap()
+    let mut num_moves = 0;
+    let mut num_rocks: usize = 0;
+    // The falling rock, if any, and whether a jet pushes it next.
+    let mut falling: Option<(Position, bool)> = None;
+    iter::from_fn(move || {
+        let rock = &rocks[num_rocks.saturating_sub(1) % rocks.len()];
+        let (caption, position) = match falling.take() {
+            None if num_rocks == NUM_ROCKS => return None,
+            None => {
+                num_rocks += 1;
+                let rock = &rocks[(num_rocks - 1) % rocks.len()];
+                let position = Position::new(2, chamber.first_rock_at + 3 + rock.height());
+                falling = Some((position.clone(), true));
+                return Some(Frame {
+                    caption: format!("rock {} begins falling", num_rocks),
+                    rows: chamber.draw(Some((rock, &position))),
+                });
+            }
+            Some((position, true)) => {
+                let mv = &moves[num_moves % moves.len()];
+                num_moves += 1;
+                let next = chamber.execute_move(mv, rock, &position);
+                let direction = match mv {
+                    Direction::Left => "left",
+                    Direction::Right => "right",
+                };
+                let caption = if next.x == position.x {
+                    format!("jet of gas pushes rock {}, but nothing happens", direction)
+                } else {
+                    format!("jet of gas pushes rock {}", direction)
+                };
+                falling = Some((next.clone(), false));
+                (caption, next)
+            }
+            Some((mut position, false)) => {
+                if !chamber.can_move_down(rock, &position) {
+                    chamber.rest_rock(rock, &position);
+                    return Some(Frame {
+                        caption: format!("rock {} comes to rest", num_rocks),
+                        rows: chamber.draw(None),
+                    });
+                }
+                position.y -= 1;
+                falling = Some((position.clone(), true));
+                (String::from("rock falls 1 unit"), position)
+            }
+        };
+        Some(Frame {
+            caption,
+            rows: chamber.draw(Some((rock, &position))),
+        })
+    })
 }
 
 #[cfg(test)]
@@ -313,7 +458,31 @@ mod tests {
     static INPUT: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
 
     #[test]
-    fn test_tower_height() {
-        assert_eq!(tower_height(INPUT), 3068);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 3068);
+        assert_eq!(parse("<>x"), Err(ParseJetsError));
+    }
+
+    #[test]
+    fn test_frames() {
+        let first: Vec<Frame> = frames(INPUT).take(9).collect();
+        assert_eq!(first[0].caption, "rock 1 begins falling");
+        assert_eq!(first[1].caption, "jet of gas pushes rock right");
+        assert_eq!(first[8].caption, "rock 1 comes to rest");
+        assert_eq!(first[8].rows, vec!["|..####.| 1", "+-------+"]);
+        let next = frames(INPUT).nth(9).unwrap();
+        assert_eq!(
+            next.rows,
+            vec![
+                "|...@...| 7",
+                "|..@@@..| 6",
+                "|...@...| 5",
+                "|.......| 4",
+                "|.......| 3",
+                "|.......| 2",
+                "|..####.| 1",
+                "+-------+",
+            ]
+        );
     }
 }
@@ -1,85 +1,44 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
 use std::num::ParseIntError;
-use std::ops::Sub;
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd)]
-struct Point {
-    x: i64,
-    y: i64,
-    z: i64,
-}
-
-impl Sub for Point {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self::Output {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-            z: self.z - other.z,
-        }
-    }
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
 }
 
 impl Point {
-    fn abs(&self) -> Self {
-        Point {
-            x: self.x.abs(),
-            y: self.y.abs(),
-            z: self.z.abs(),
-        }
-    }
-
-    fn is_adjacent(&self, other: &Point) -> bool {
-        let abs = (*self - *other).abs();
-        abs.x + abs.y + abs.z == 1
+    pub fn new(x: i64, y: i64, z: i64) -> Self {
+        Point { x, y, z }
     }
 
     fn neighbors(&self) -> [Point; 6] {
         [
-            Point {
-                x: self.x - 1,
-                y: self.y,
-                z: self.z,
-            },
-            Point {
-                x: self.x + 1,
-                y: self.y,
-                z: self.z,
-            },
-            Point {
-                x: self.x,
-                y: self.y - 1,
-                z: self.z,
-            },
-            Point {
-                x: self.x,
-                y: self.y + 1,
-                z: self.z,
-            },
-            Point {
-                x: self.x,
-                y: self.y,
-                z: self.z - 1,
-            },
-            Point {
-                x: self.x,
-                y: self.y,
-                z: self.z + 1,
-            },
+            Point::new(self.x - 1, self.y, self.z),
+            Point::new(self.x + 1, self.y, self.z),
+            Point::new(self.x, self.y - 1, self.z),
+            Point::new(self.x, self.y + 1, self.z),
+            Point::new(self.x, self.y, self.z - 1),
+            Point::new(self.x, self.y, self.z + 1),
         ]
     }
 
-    fn is_within(&self, bound: i64) -> bool {
+    fn coords(&self) -> [i64; 3] {
         [self.x, self.y, self.z]
-            .iter()
-            .all(|&d| -1 <= d && d <= bound)
+    }
+
+    fn is_within(&self, min: &Point, max: &Point) -> bool {
+        (min.x..=max.x).contains(&self.x)
+            && (min.y..=max.y).contains(&self.y)
+            && (min.z..=max.z).contains(&self.z)
     }
 }
 
 #[derive(Debug)]
-struct ParsePointError;
+pub struct ParsePointError;
 
 impl From<ParseIntError> for ParsePointError {
     fn from(_: ParseIntError) -> Self {
@@ -91,137 +50,232 @@ impl FromStr for Point {
     type Err = ParsePointError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokens: Vec<&str> = s.split(',').collect();
+        let tokens: Vec<&str> = s.trim().split(',').collect();
         if tokens.len() != 3 {
             return Err(ParsePointError);
         }
         Ok(Point {
-            x: tokens[0].parse()?,
-            y: tokens[1].parse()?,
-            z: tokens[2].parse()?,
+            x: tokens[0].trim().parse()?,
+            y: tokens[1].trim().parse()?,
+            z: tokens[2].trim().parse()?,
         })
     }
 }
 
-pub fn surface_area(s: &str) -> usize {
-    let points: Vec<Point> = s
-        .trim()
-        .split('\n')
-        .map(|s| {
-            s.parse()
-                .unwrap_or_else(|_| panic!("failed to parse point: {}", s))
-        })
-        .collect();
-    let mut adjacent_pairs: HashSet<(Point, Point)> = HashSet::new();
-    for i in 0..points.len() {
-        for j in 0..points.len() {
-            if i == j {
-                continue;
+/// A connected region of air fully enclosed by the droplet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AirPocket {
+    pub cells: Vec<Point>,
+}
+
+impl AirPocket {
+    pub fn volume(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+/// A set of unit cubes, each identified by its minimum corner.
+#[derive(Debug, Clone)]
+pub struct VoxelSet {
+    voxels: HashSet<Point>,
+    min: Point,
+    max: Point,
+}
+
+impl FromStr for VoxelSet {
+    type Err = ParsePointError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let points = s
+            .trim()
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.parse())
+            .collect::<Result<Vec<Point>, _>>()?;
+        Ok(VoxelSet::from_points(points))
+    }
+}
+
+impl VoxelSet {
+    pub fn from_points<I: IntoIterator<Item = Point>>(points: I) -> Self {
+        let voxels: HashSet<Point> = points.into_iter().collect();
+        let (min, max) = voxels.iter().fold(
+            (
+                Point::new(i64::MAX, i64::MAX, i64::MAX),
+                Point::new(i64::MIN, i64::MIN, i64::MIN),
+            ),
+            |(min, max), p| {
+                (
+                    Point::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                    Point::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+                )
+            },
+        );
+        VoxelSet { voxels, min, max }
+    }
+
+    pub fn len(&self) -> usize {
+        self.voxels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.voxels.is_empty()
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        self.voxels.contains(p)
+    }
+
+    // Return the bounding box grown by one unit on every side, so that the
+    // outside air forms a single connected shell around the droplet.
+    fn padded_bounds(&self) -> (Point, Point) {
+        (
+            Point::new(self.min.x - 1, self.min.y - 1, self.min.z - 1),
+            Point::new(self.max.x + 1, self.max.y + 1, self.max.z + 1),
+        )
+    }
+
+    // Flood fill the air connected to the given cell within the given bounds.
+    fn flood_fill(&self, from: Point, min: &Point, max: &Point) -> HashSet<Point> {
+        let mut discovered = HashSet::from([from]);
+        let mut stack = vec![from];
+        while let Some(point) = stack.pop() {
+            for n in point.neighbors() {
+                if n.is_within(min, max) && !self.voxels.contains(&n) && discovered.insert(n) {
+                    stack.push(n);
+                }
             }
-            if points[i].is_adjacent(&points[j]) {
-                adjacent_pairs.insert(if points[i] < points[j] {
-                    (points[i], points[j])
-                } else {
-                    (points[j], points[i])
-                });
+        }
+        discovered
+    }
+
+    // Return the air cells reachable from outside the droplet.
+    fn exterior(&self) -> HashSet<Point> {
+        if self.is_empty() {
+            return HashSet::new();
+        }
+        let (min, max) = self.padded_bounds();
+        self.flood_fill(min, &min, &max)
+    }
+
+    /// Return the number of faces not touching another voxel.
+    pub fn surface_area(&self) -> usize {
+        self.voxels
+            .iter()
+            .flat_map(|p| p.neighbors())
+            .filter(|n| !self.voxels.contains(n))
+            .count()
+    }
+
+    /// Return the number of faces reachable from outside the droplet.
+    pub fn exterior_surface_area(&self) -> usize {
+        let exterior = self.exterior();
+        self.voxels
+            .iter()
+            .flat_map(|p| p.neighbors())
+            .filter(|n| exterior.contains(n))
+            .count()
+    }
+
+    /// Return the enclosed air pockets, largest first.
+    pub fn air_pockets(&self) -> Vec<AirPocket> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let (min, max) = (self.min, self.max);
+        let mut seen = self.exterior();
+        let mut pockets = Vec::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    let p = Point::new(x, y, z);
+                    if self.voxels.contains(&p) || seen.contains(&p) {
+                        continue;
+                    }
+                    let region = self.flood_fill(p, &min, &max);
+                    let mut cells: Vec<Point> = region.into_iter().collect();
+                    cells.sort();
+                    seen.extend(cells.iter().copied());
+                    pockets.push(AirPocket { cells });
+                }
             }
         }
+        pockets.sort_by(|a, b| b.volume().cmp(&a.volume()).then(a.cells.cmp(&b.cells)));
+        pockets
     }
-    points.len() * 6 - adjacent_pairs.len() * 2
-}
 
-// FIXME: The result is off by one for example and way off for the actual input.
-// pub fn external_surface_area(s: &str) -> usize {
-//     let mut points: Vec<Point> = s
-//         .trim()
-//         .split('\n')
-//         .map(|s| {
-//             s.parse()
-//                 .unwrap_or_else(|_| panic!("failed to parse point: {}", s))
-//         })
-//         .collect();
-//     let (min_x, max_x, min_y, max_y, min_z, max_z) = points.iter().fold(
-//         (i64::MAX, i64::MIN, i64::MAX, i64::MIN, i64::MAX, i64::MIN),
-//         |(nx, xx, ny, xy, nz, xz), p| {
-//             (
-//                 p.x.min(nx),
-//                 p.x.max(xx),
-//                 p.y.min(ny),
-//                 p.y.max(xy),
-//                 p.z.min(nz),
-//                 p.z.max(xz),
-//             )
-//         },
-//     );
-//     println!("bounds: {:?}", (min_x, max_x, min_y, max_y, min_z, max_z));
-
-//     let mut adjacent_pairs: HashMap<Point, HashMap<Point, bool>> = HashMap::new();
-//     for i in 0..points.len() {
-//         for j in 0..points.len() {
-//             if i == j || !points[i].is_adjacent(&points[j]) {
-//                 continue;
-//             }
-//             adjacent_pairs
-//                 .entry(points[i])
-//                 .or_insert_with(|| HashMap::new())
-//                 .insert(points[j], true);
-//             adjacent_pairs
-//                 .entry(points[j])
-//                 .or_insert_with(|| HashMap::new())
-//                 .insert(points[i], true);
-//         }
-//     }
-
-//     points.retain(|p| {
-//         p.x == min_x || p.x == max_x || p.y == min_y || p.y == max_y || p.z == min_z || p.z == max_z
-//     });
-
-//     points
-//         .iter()
-//         .map(|p| match adjacent_pairs.get(p) {
-//             None => return 6,
-//             Some(m) => {
-//                 println!("{:?}, {:?}", p, m);
-//                 return 6 - m.len();
-//             }
-//         })
-//         .sum()
-// }
-
-pub fn external_surface_area(s: &str) -> usize {
-    let points: HashSet<Point> = s
-        .trim()
-        .lines()
-        .map(|s| {
-            s.parse()
-                .unwrap_or_else(|_| panic!("failed to parse point: {}", s))
-        })
-        .collect();
-    let bound = points
-        .iter()
-        .fold(i64::MIN, |max, p| p.x.max(p.y.max(p.z.max(max))))
-        + 1;
-    // DFS idea, courtesy: u/SuperSmurfen
-    let mut discovered: HashSet<Point> = HashSet::new();
-    let mut stack = vec![Point { x: 0, y: 0, z: 0 }];
-    while !stack.is_empty() {
-        for point in stack.pop().unwrap().neighbors() {
-            if !points.contains(&point) && !discovered.contains(&point) && point.is_within(bound) {
-                discovered.insert(point);
-                stack.push(point);
+    /// Write the exterior faces of the droplet as a Wavefront OBJ mesh.
+    ///
+    /// Vertices are shared between faces and every face is wound
+    /// counter-clockwise when viewed from outside the droplet.
+    pub fn write_obj<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let exterior = self.exterior();
+        let mut voxels: Vec<&Point> = self.voxels.iter().collect();
+        voxels.sort();
+
+        let mut vertices: HashMap<[i64; 3], usize> = HashMap::new();
+        let mut ordered: Vec<[i64; 3]> = Vec::new();
+        let mut faces: Vec<[usize; 4]> = Vec::new();
+        for p in voxels {
+            for axis in 0..3 {
+                for sign in [-1, 1] {
+                    let mut n = p.coords();
+                    n[axis] += sign;
+                    if !exterior.contains(&Point::new(n[0], n[1], n[2])) {
+                        continue;
+                    }
+                    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                    let mut base = p.coords();
+                    if sign > 0 {
+                        base[axis] += 1;
+                    }
+                    let mut corners = [base; 4];
+                    corners[1][u] += 1;
+                    corners[2][u] += 1;
+                    corners[2][v] += 1;
+                    corners[3][v] += 1;
+                    if sign < 0 {
+                        corners.reverse();
+                    }
+                    let mut face = [0; 4];
+                    for (i, corner) in corners.iter().enumerate() {
+                        face[i] = *vertices.entry(*corner).or_insert_with(|| {
+                            ordered.push(*corner);
+                            ordered.len()
+                        });
+                    }
+                    faces.push(face);
+                }
             }
         }
+
+        for [x, y, z] in ordered {
+            writeln!(w, "v {} {} {}", x, y, z)?;
+        }
+        for [a, b, c, d] in faces {
+            writeln!(w, "f {} {} {} {}", a, b, c, d)?;
+        }
+        Ok(())
     }
-    points
-        .iter()
-        .flat_map(|&p| p.neighbors())
-        .filter(|n| discovered.contains(n))
-        .count()
+}
+
+/// Parse the cubes of the droplet.
+pub fn parse(s: &str) -> Result<VoxelSet, ParsePointError> {
+    s.parse()
+}
+
+pub fn part1(voxels: &VoxelSet) -> usize {
+    voxels.surface_area()
+}
+
+pub fn part2(voxels: &VoxelSet) -> usize {
+    voxels.exterior_surface_area()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     static INPUT: &str = "2,2,2
 1,2,2
@@ -238,12 +292,141 @@ mod tests {
 2,3,5";
 
     #[test]
-    fn test_surface_area() {
-        assert_eq!(surface_area(INPUT), 64);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 64);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 58);
+    }
+
+    #[test]
+    fn test_negative_coordinates() {
+        let shifted = VoxelSet::from_points(
+            parse(INPUT)
+                .unwrap()
+                .voxels
+                .iter()
+                .map(|p| Point::new(p.x - 10, p.y - 3, p.z - 7)),
+        );
+        assert_eq!(shifted.surface_area(), 64);
+        assert_eq!(shifted.exterior_surface_area(), 58);
     }
 
     #[test]
-    fn test_external_surface_area() {
-        assert_eq!(external_surface_area(INPUT), 58);
+    fn test_air_pockets() {
+        let pockets = parse(INPUT).unwrap().air_pockets();
+        assert_eq!(
+            pockets,
+            vec![AirPocket {
+                cells: vec![Point::new(2, 2, 5)]
+            }]
+        );
+
+        // A solid 5x3x4 block with a 1x1x1 and a separate 1x1x2 cavity.
+        let mut points = Vec::new();
+        for x in 0..5 {
+            for y in 0..3 {
+                for z in 0..4 {
+                    points.push(Point::new(x, y, z));
+                }
+            }
+        }
+        let cavities = [(1, 1, 1), (3, 1, 1), (3, 1, 2)];
+        points.retain(|p| !cavities.contains(&(p.x, p.y, p.z)));
+        let volumes: Vec<usize> = VoxelSet::from_points(points)
+            .air_pockets()
+            .iter()
+            .map(|p| p.volume())
+            .collect();
+        assert_eq!(volumes, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_write_obj() {
+        let mut obj = Vec::new();
+        parse("0,0,0").unwrap().write_obj(&mut obj).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 8);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 6);
+
+        let mut obj = Vec::new();
+        parse(INPUT).unwrap().write_obj(&mut obj).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 58);
+    }
+
+    // Generate droplets of up to 80 cubes around the origin.
+    fn droplet() -> impl Strategy<Value = String> {
+        prop::collection::vec((-4i64..4, -4i64..4, -4i64..4), 0..80).prop_map(|cubes| {
+            cubes
+                .iter()
+                .map(|(x, y, z)| format!("{},{},{}", x, y, z))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+
+    // Count faces by checking every pair of cubes for adjacency.
+    fn pairwise_surface_area(points: &[Point]) -> usize {
+        let mut points = points.to_vec();
+        points.sort();
+        points.dedup();
+        let mut adjacent = 0;
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                let (a, b) = (points[i], points[j]);
+                if (a.x - b.x).abs() + (a.y - b.y).abs() + (a.z - b.z).abs() == 1 {
+                    adjacent += 1;
+                }
+            }
+        }
+        points.len() * 6 - adjacent * 2
+    }
+
+    // Count exterior faces by flood filling a generously padded cube.
+    fn flood_fill_exterior_surface_area(points: &[Point]) -> usize {
+        let cubes: HashSet<Point> = points.iter().copied().collect();
+        let (min, max) = (Point::new(-6, -6, -6), Point::new(6, 6, 6));
+        let mut outside = HashSet::from([min]);
+        let mut stack = vec![min];
+        while let Some(p) = stack.pop() {
+            for n in p.neighbors() {
+                if n.is_within(&min, &max) && !cubes.contains(&n) && outside.insert(n) {
+                    stack.push(n);
+                }
+            }
+        }
+        cubes
+            .iter()
+            .flat_map(|p| p.neighbors())
+            .filter(|n| outside.contains(n))
+            .count()
+    }
+
+    proptest! {
+        #[test]
+        fn prop_surface_area(s in droplet()) {
+            let points: Vec<Point> = s.lines().map(|l| l.parse().unwrap()).collect();
+            let voxels = parse(&s).unwrap();
+            prop_assert_eq!(voxels.surface_area(), pairwise_surface_area(&points));
+            prop_assert_eq!(
+                voxels.exterior_surface_area(),
+                flood_fill_exterior_surface_area(&points)
+            );
+
+            // Every face is either exterior or touches exactly one air pocket.
+            let pocket_faces: usize = voxels
+                .air_pockets()
+                .iter()
+                .flat_map(|pocket| pocket.cells.iter().flat_map(|c| c.neighbors()))
+                .filter(|n| voxels.contains(n))
+                .count();
+            prop_assert_eq!(
+                voxels.surface_area(),
+                voxels.exterior_surface_area() + pocket_faces
+            );
+        }
     }
 }
@@ -1,17 +1,46 @@
-fn sum_grove_coordinates(s: &str, key: i64, num_iterations: usize) -> i64 {
+use crate::progress::{NoProgress, Progress};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseFileError;
+
+/// Parse the numbers of the encrypted file, which must include a zero.
+pub fn parse(s: &str) -> Result<Vec<i64>, ParseFileError> {
     let nums: Vec<i64> = s
         .trim()
         .lines()
-        .map(|s| s.parse::<i64>().unwrap() * key)
-        .collect();
+        .map(|s| s.parse().map_err(|_| ParseFileError))
+        .collect::<Result<_, _>>()?;
+    if !nums.contains(&0) {
+        return Err(ParseFileError);
+    }
+    Ok(nums)
+}
+
+/// Return the sum of the grove coordinates after applying the decryption key
+/// and mixing the given number of times. Progress is reported after moving
+/// each number.
+pub fn sum_grove_coordinates(
+    nums: &[i64],
+    key: i64,
+    num_iterations: usize,
+    progress: &dyn Progress,
+) -> i64 {
+    let nums: Vec<i64> = nums.iter().map(|n| n * key).collect();
     let mut idxs: Vec<usize> = (0..nums.len()).collect();
 
-    for _ in 0..num_iterations {
+    let total = num_iterations * nums.len();
+    for round in 0..num_iterations {
         for (i, &n) in nums.iter().enumerate() {
             let j = idxs.iter().position(|&n| n == i).unwrap();
             idxs.remove(j);
             let to = (j as i64 + n).rem_euclid(idxs.len() as i64) as usize;
             idxs.insert(to, i);
+            progress.update(
+                "number",
+                round * nums.len() + i + 1,
+                Some(total),
+                ("round", round + 1),
+            );
         }
     }
 
@@ -26,17 +55,22 @@ fn sum_grove_coordinates(s: &str, key: i64, num_iterations: usize) -> i64 {
         .sum()
 }
 
-pub fn sum_grove_coordinates_p1(s: &str) -> i64 {
-    sum_grove_coordinates(s, 1, 1)
+pub const DECRYPTION_KEY: i64 = 811589153;
+pub const ROUNDS: usize = 10;
+
+pub fn part1(nums: &[i64]) -> i64 {
+    sum_grove_coordinates(nums, 1, 1, &NoProgress)
 }
 
-pub fn sum_grove_coordinates_p2(s: &str) -> i64 {
-    sum_grove_coordinates(s, 811589153, 10)
+pub fn part2(nums: &[i64]) -> i64 {
+    sum_grove_coordinates(nums, DECRYPTION_KEY, ROUNDS, &NoProgress)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use std::collections::VecDeque;
 
     static INPUT: &str = "1
 2
@@ -47,12 +81,64 @@ mod tests {
 4";
 
     #[test]
-    fn test_sum_grove_coordinates() {
-        assert_eq!(sum_grove_coordinates_p1(INPUT), 3);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 3);
+        assert_eq!(parse("1\n2"), Err(ParseFileError));
     }
 
     #[test]
-    fn test_sum_grove_coordinates_p2() {
-        assert_eq!(sum_grove_coordinates_p2(INPUT), 1623178306);
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 1623178306);
+    }
+
+    // Generate mixing lists with exactly one zero.
+    fn mixing_list() -> impl Strategy<Value = String> {
+        (prop::collection::vec((-30i64..30).prop_filter("non-zero", |&n| n != 0), 1..40))
+            .prop_flat_map(|nums| {
+                let len = nums.len();
+                (Just(nums), 0..=len)
+            })
+            .prop_map(|(mut nums, zero_at)| {
+                nums.insert(zero_at, 0);
+                nums.iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+    }
+
+    // Mix by rotating a list so the moved number is always at the front.
+    fn naive_sum_grove_coordinates(s: &str, key: i64, num_iterations: usize) -> i64 {
+        let nums: Vec<i64> = s.lines().map(|l| l.parse::<i64>().unwrap() * key).collect();
+        let mut list: VecDeque<(usize, i64)> = nums.iter().copied().enumerate().collect();
+        for _ in 0..num_iterations {
+            for (i, &n) in nums.iter().enumerate() {
+                let at = list.iter().position(|&(j, _)| j == i).unwrap();
+                list.rotate_left(at);
+                let moved = list.pop_front().unwrap();
+                list.rotate_left(n.rem_euclid(list.len() as i64) as usize);
+                list.push_front(moved);
+            }
+        }
+        let zero_at = list.iter().position(|&(_, n)| n == 0).unwrap();
+        [1000, 2000, 3000]
+            .iter()
+            .map(|p| list[(zero_at + p) % list.len()].1)
+            .sum()
+    }
+
+    proptest! {
+        #[test]
+        fn prop_sum_grove_coordinates(s in mixing_list()) {
+            let nums = parse(&s).unwrap();
+            prop_assert_eq!(
+                part1(&nums),
+                naive_sum_grove_coordinates(&s, 1, 1)
+            );
+            prop_assert_eq!(
+                part2(&nums),
+                naive_sum_grove_coordinates(&s, 811589153, 10)
+            );
+        }
     }
 }
@@ -3,6 +3,9 @@ use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseJobError;
+
 #[derive(Clone)]
 enum Operation {
     Multiply,
@@ -12,7 +15,7 @@ enum Operation {
 }
 
 impl FromStr for Operation {
-    type Err = ();
+    type Err = ParseJobError;
 
     fn from_str(s: &str) -> Result<Operation, Self::Err> {
         match s {
@@ -20,7 +23,7 @@ impl FromStr for Operation {
             "/" => Ok(Self::Divide),
             "+" => Ok(Self::Add),
             "-" => Ok(Self::Subtract),
-            _ => Err(()),
+            _ => Err(ParseJobError),
         }
     }
 }
@@ -54,20 +57,22 @@ enum Value {
 }
 
 impl FromStr for Value {
-    type Err = ();
+    type Err = ParseJobError;
 
     fn from_str(s: &str) -> Result<Value, Self::Err> {
         let tokens: Vec<&str> = s.trim().split(' ').collect();
         if tokens.len() == 1 {
-            Ok(Value::Integer(tokens[0].parse().ok().ok_or(())?))
+            Ok(Value::Integer(
+                tokens[0].parse().map_err(|_| ParseJobError)?,
+            ))
         } else if tokens.len() == 3 {
             Ok(Value::Op(
-                tokens[1].parse().ok().ok_or(())?,
+                tokens[1].parse()?,
                 tokens[0].to_string(),
                 tokens[2].to_string(),
             ))
         } else {
-            Err(())
+            Err(ParseJobError)
         }
     }
 }
@@ -78,22 +83,23 @@ struct Job {
 }
 
 impl FromStr for Job {
-    type Err = ();
+    type Err = ParseJobError;
 
     fn from_str(s: &str) -> Result<Job, Self::Err> {
-        let (monkey, value) = s.trim().split_once(':').ok_or(())?;
+        let (monkey, value) = s.trim().split_once(':').ok_or(ParseJobError)?;
         let monkey = monkey.to_string();
         let value: Value = value.parse()?;
         Ok(Job { monkey, value })
     }
 }
 
-struct JobTable {
+/// The job of every monkey, by name.
+pub struct JobTable {
     jobs: HashMap<String, Job>,
 }
 
 impl FromStr for JobTable {
-    type Err = ();
+    type Err = ParseJobError;
 
     fn from_str(s: &str) -> Result<JobTable, Self::Err> {
         let mut jobs: HashMap<String, Job> = HashMap::new();
@@ -101,6 +107,14 @@ impl FromStr for JobTable {
             let job: Job = line.parse()?;
             jobs.insert(job.monkey.clone(), job);
         }
+        let known = |monkey: &String| jobs.contains_key(monkey);
+        let valid = jobs.values().all(|job| match &job.value {
+            Value::Integer(_) => true,
+            Value::Op(_, left, right) => known(left) && known(right),
+        });
+        if !valid || !jobs.contains_key("root") {
+            return Err(ParseJobError);
+        }
         Ok(JobTable { jobs })
     }
 }
@@ -150,19 +164,23 @@ impl JobTable {
     }
 }
 
-pub fn root_yells(s: &str) -> i64 {
-    let table: JobTable = s.parse().unwrap();
+/// Parse the job of each monkey.
+pub fn parse(s: &str) -> Result<JobTable, ParseJobError> {
+    s.parse()
+}
+
+pub fn part1(table: &JobTable) -> i64 {
     table.yells("root")
 }
 
-pub fn i_yell(s: &str) -> i64 {
-    let table: JobTable = s.parse().unwrap();
+pub fn part2(table: &JobTable) -> i64 {
     table.i_yell()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     static INPUT: &str = "root: pppw + sjmn
 dbpl: 5
@@ -181,12 +199,106 @@ drzm: hmdt - zczc
 hmdt: 32";
 
     #[test]
-    fn test_root_yells() {
-        assert_eq!(root_yells(INPUT), 152);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 152);
+        assert!(parse("root: abcd + efgh\nabcd: 1").is_err());
     }
 
     #[test]
-    fn test_i_yell() {
-        assert_eq!(i_yell(INPUT), 301);
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 301);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Expr {
+        Number(i64),
+        Op(char, Box<Expr>, Box<Expr>),
+    }
+
+    impl Expr {
+        fn evaluate(&self) -> i64 {
+            match self {
+                Expr::Number(n) => *n,
+                Expr::Op(op, left, right) => {
+                    let (left, right) = (left.evaluate(), right.evaluate());
+                    match op {
+                        '+' => left + right,
+                        '-' => left - right,
+                        '*' => left * right,
+                        _ => left / right,
+                    }
+                }
+            }
+        }
+
+        // Add a job for every node below and including this one, returning the
+        // name of the monkey yelling the result. Monkeys below are named by
+        // counting up from `next`.
+        fn jobs(&self, name: String, next: &mut usize, lines: &mut Vec<String>) -> String {
+            let job = match self {
+                Expr::Number(n) => n.to_string(),
+                Expr::Op(op, left, right) => {
+                    *next += 2;
+                    let (left_name, right_name) = (monkey_name(*next - 2), monkey_name(*next - 1));
+                    let left = left.jobs(left_name, next, lines);
+                    let right = right.jobs(right_name, next, lines);
+                    format!("{} {} {}", left, op, right)
+                }
+            };
+            lines.push(format!("{}: {}", name, job));
+            name
+        }
+    }
+
+    fn monkey_name(mut i: usize) -> String {
+        (0..4)
+            .map(|_| {
+                let c = (b'a' + (i % 26) as u8) as char;
+                i /= 26;
+                c
+            })
+            .collect()
+    }
+
+    // Generate expressions of depth at most 4 over single digits, dividing
+    // only by non-zero literals so every job table has a well-defined answer.
+    fn expr() -> impl Strategy<Value = Expr> {
+        (0i64..10)
+            .prop_map(Expr::Number)
+            .prop_recursive(4, 16, 2, |inner| {
+                prop_oneof![
+                    (
+                        prop_oneof![Just('+'), Just('-'), Just('*')],
+                        inner.clone(),
+                        inner.clone()
+                    )
+                        .prop_map(|(op, l, r)| Expr::Op(
+                            op,
+                            Box::new(l),
+                            Box::new(r)
+                        )),
+                    (inner, 1i64..10).prop_map(|(l, r)| Expr::Op(
+                        '/',
+                        Box::new(l),
+                        Box::new(Expr::Number(r))
+                    )),
+                ]
+            })
+    }
+
+    fn job_table() -> impl Strategy<Value = (Expr, String)> {
+        expr().prop_flat_map(|expr| {
+            let mut lines = Vec::new();
+            expr.jobs(String::from("root"), &mut 0, &mut lines);
+            (Just(expr), Just(lines).prop_shuffle())
+                .prop_map(|(expr, lines)| (expr, lines.join("\n")))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_root_yells((expr, input) in job_table()) {
+            prop_assert_eq!(part1(&parse(&input).unwrap()), expr.evaluate());
+        }
     }
 }
@@ -79,7 +79,7 @@ impl Map {
         }
     }
 
-    fn execute(&self, instructions: &Vec<Instruction>) -> i64 {
+    fn execute(&self, instructions: &[Instruction]) -> i64 {
         let mut facing = Facing::Right;
         let mut row = 0;
         let mut col = self.tiles[row]
@@ -192,11 +192,37 @@ impl Instruction {
     }
 }
 
-pub fn final_password(s: &str) -> i64 {
-    let (map, path) = s.split_once("\n\n").unwrap();
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseNotesError;
+
+/// The map of the board and the path to follow on it.
+#[derive(Debug)]
+pub struct Notes {
+    map: Map,
+    instructions: Vec<Instruction>,
+}
+
+/// Parse the map and the path.
+pub fn parse(s: &str) -> Result<Notes, ParseNotesError> {
+    let (map, path) = s.split_once("\n\n").ok_or(ParseNotesError)?;
+    let path = path.trim();
+    if path.is_empty()
+        || !path
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == 'L' || c == 'R')
+    {
+        return Err(ParseNotesError);
+    }
     let map = Map::from_str(map);
+    if !matches!(map.tiles.first(), Some(row) if row.contains(&Tile::Open)) {
+        return Err(ParseNotesError);
+    }
     let instructions = Instruction::parse_instructions(path);
-    map.execute(&instructions)
+    Ok(Notes { map, instructions })
+}
+
+pub fn part1(notes: &Notes) -> i64 {
+    notes.map.execute(&notes.instructions)
 }
 
 #[cfg(test)]
@@ -220,7 +246,7 @@ mod tests {
 ";
 
     #[test]
-    fn test_final_password() {
-        assert_eq!(final_password(INPUT), 6032);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 6032);
     }
 }
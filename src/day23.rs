@@ -1,5 +1,9 @@
+use crate::cancel::{Cancelled, Token};
+use crate::progress::{NoProgress, Progress};
+use crate::visualize::Frame;
 use std::collections::HashMap;
 use std::fmt;
+use std::iter;
 use std::ops::Add;
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd)]
@@ -55,19 +59,43 @@ impl fmt::Debug for Map {
     }
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseScanError;
+
+/// The scanned tiles of the grove, before any round.
+#[derive(Debug, Clone)]
+pub struct Scan {
+    tiles: Vec<Vec<Tile>>,
+}
+
+/// Parse the scan of the grove.
+pub fn parse(s: &str) -> Result<Scan, ParseScanError> {
+    let tiles: Vec<Vec<Tile>> = s
+        .trim()
+        .lines()
+        .map(|line| {
+            line.chars()
+                .map(|ch| match ch {
+                    '.' => Ok(Tile::Empty),
+                    '#' => Ok(Tile::Elf),
+                    _ => Err(ParseScanError),
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    if tiles.is_empty() || tiles.iter().any(|row| row.len() != tiles[0].len()) {
+        return Err(ParseScanError);
+    }
+    Ok(Scan { tiles })
+}
+
 impl Map {
-    fn from_str(s: &str, padding: usize) -> Map {
-        let num_rows = s.lines().count() + 2 * padding;
-        let num_cols = s.lines().next().unwrap().len() + 2 * padding;
+    fn new(scan: &Scan, padding: usize) -> Map {
+        let num_rows = scan.tiles.len() + 2 * padding;
+        let num_cols = scan.tiles[0].len() + 2 * padding;
         let mut tiles = vec![vec![Tile::Empty; num_cols]; num_rows];
-        for (r, line) in s.lines().enumerate() {
-            for (c, ch) in line.chars().enumerate() {
-                tiles[padding + r][padding + c] = match ch {
-                    '.' => Tile::Empty,
-                    '#' => Tile::Elf,
-                    _ => unreachable!(),
-                }
-            }
+        for (r, row) in scan.tiles.iter().enumerate() {
+            tiles[padding + r][padding..padding + row.len()].copy_from_slice(row);
         }
         let proposal_order = [
             (
@@ -171,15 +199,23 @@ impl Map {
         num_moves
     }
 
-    fn simulate(&mut self, count: usize) -> usize {
+    fn simulate(
+        &mut self,
+        count: usize,
+        token: &Token,
+        progress: &dyn Progress,
+    ) -> Result<usize, Cancelled> {
+        let total = (count != usize::MAX).then_some(count);
         let mut n = 1;
         loop {
+            token.check()?;
             let num_moves = self.round(n);
+            progress.update("round", n, total, ("moves", num_moves));
             if count == n {
                 break;
             }
             if num_moves == 0 {
-                return n;
+                return Ok(n);
             }
             n += 1;
         }
@@ -193,7 +229,7 @@ impl Map {
                 }
             }
         }
-        result
+        Ok(result)
     }
 
     fn bounds(&self) -> (usize, usize, usize, usize) {
@@ -211,18 +247,93 @@ impl Map {
         }
         (min_row, min_col, max_row, max_col)
     }
+
+    // Draw the tiles within the given bounds.
+    fn rows(
+        &self,
+        (min_row, min_col, max_row, max_col): (usize, usize, usize, usize),
+    ) -> Vec<String> {
+        self.tiles[min_row..=max_row]
+            .iter()
+            .map(|row| {
+                row[min_col..=max_col]
+                    .iter()
+                    .map(|tile| match tile {
+                        Tile::Empty => '.',
+                        Tile::Elf => '#',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
 }
 
-pub fn empty_ground_tiles(s: &str) -> usize {
+/// Return the number of empty ground tiles in the smallest rectangle holding
+/// every elf after ten rounds.
+pub fn empty_ground_tiles(
+    scan: &Scan,
+    token: &Token,
+    progress: &dyn Progress,
+) -> Result<usize, Cancelled> {
     // HACK: Padding was figured out based on out of bounds error.
-    let mut map = Map::from_str(s, 6);
-    map.simulate(10)
+    let mut map = Map::new(scan, 6);
+    map.simulate(10, token, progress)
 }
 
-pub fn first_idle_round(s: &str) -> usize {
+/// Return the first round in which no elf moves, checking the token before
+/// each round and reporting the number of moves after it.
+pub fn first_idle_round(
+    scan: &Scan,
+    token: &Token,
+    progress: &dyn Progress,
+) -> Result<usize, Cancelled> {
     // HACK: Padding was figured out based on out of bounds error.
-    let mut map = Map::from_str(s, 60);
-    map.simulate(usize::MAX)
+    let mut map = Map::new(scan, 60);
+    map.simulate(usize::MAX, token, progress)
+}
+
+pub fn part1(scan: &Scan) -> usize {
+    empty_ground_tiles(scan, &Token::default(), &NoProgress).unwrap()
+}
+
+pub fn part2(scan: &Scan) -> usize {
+    first_idle_round(scan, &Token::default(), &NoProgress).unwrap()
+}
+
+/// Return a frame of the initial state and of the end of each round, for the
+/// given number of rounds or until no elf moves.
+pub fn frames(s: &str, num_rounds: Option<usize>) -> impl Iterator<Item = Frame> {
+    let scan = parse(s).unwrap_or_else(|e| panic!("failed to parse scan: {:?}", e));
+    let mut map = Map::new(&scan, if num_rounds.is_some() { 6 } else { 60 });
+    // Draw every frame within all the bounds so far so that the view only
+    // ever grows.
+    let mut bounds = map.bounds();
+    let mut n = 0;
+    let mut idle = false;
+    iter::from_fn(move || {
+        if idle || matches!(num_rounds, Some(r) if n > r) {
+            return None;
+        }
+        let caption = if n == 0 {
+            String::from("initial state")
+        } else {
+            let num_moves = map.round(n);
+            idle = num_moves == 0;
+            format!("end of round {}, {} elves moved", n, num_moves)
+        };
+        n += 1;
+        let (min_row, min_col, max_row, max_col) = map.bounds();
+        bounds = (
+            bounds.0.min(min_row),
+            bounds.1.min(min_col),
+            bounds.2.max(max_row),
+            bounds.3.max(max_col),
+        );
+        Some(Frame {
+            caption,
+            rows: map.rows(bounds),
+        })
+    })
 }
 
 #[cfg(test)]
@@ -238,12 +349,30 @@ mod tests {
 .#..#..";
 
     #[test]
-    fn test_empty_ground_tiles() {
-        assert_eq!(empty_ground_tiles(INPUT), 110);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 110);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 20);
     }
 
     #[test]
-    fn test_first_idle_round() {
-        assert_eq!(first_idle_round(INPUT), 20);
+    fn test_frames() {
+        let all: Vec<Frame> = frames(INPUT, None).collect();
+        assert_eq!(all.len(), 21);
+        assert_eq!(all[20].caption, "end of round 20, 0 elves moved");
+        let last = all.last().unwrap();
+        let elves = last
+            .rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .filter(|&c| c == '#');
+        assert_eq!(elves.count(), 22);
+        assert_eq!(
+            frames(INPUT, Some(10)).last().unwrap().caption,
+            "end of round 10, 9 elves moved"
+        );
     }
 }
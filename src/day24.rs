@@ -1,25 +1,25 @@
-use std::collections::HashSet;
+use crate::cancel::{Cancelled, Token};
+use crate::progress::{NoProgress, Progress};
+use crate::visualize::Frame;
+use pathfinding::prelude::astar;
+use std::collections::HashMap;
+use std::iter;
 use std::ops::Add;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd)]
-struct Position {
-    row: i64,
-    col: i64,
+pub struct Position {
+    pub row: i64,
+    pub col: i64,
 }
 
 impl Position {
-    fn new(row: i64, col: i64) -> Position {
+    pub fn new(row: i64, col: i64) -> Position {
         Position { row, col }
     }
 
-    fn neighbors(&self) -> [Position; 5] {
-        [
-            Position::new(self.row - 1, self.col),
-            Position::new(self.row + 1, self.col),
-            Position::new(self.row, self.col - 1),
-            Position::new(self.row, self.col + 1),
-            Position::new(self.row, self.col),
-        ]
+    fn manhattan_distance(&self, other: &Position) -> u64 {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
     }
 }
 
@@ -34,96 +34,376 @@ impl Add for Position {
     }
 }
 
+/// A single minute of the expedition.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+    Wait,
+}
+
+impl Move {
+    const ALL: [Move; 5] = [Move::Up, Move::Down, Move::Left, Move::Right, Move::Wait];
+
+    fn offset(&self) -> Position {
+        match self {
+            Move::Up => Position::new(-1, 0),
+            Move::Down => Position::new(1, 0),
+            Move::Left => Position::new(0, -1),
+            Move::Right => Position::new(0, 1),
+            Move::Wait => Position::new(0, 0),
+        }
+    }
+}
+
+// A fixed-width bitset per (line, minute), where a line is a row or column of
+// the basin and the minute is taken modulo the period of that line.
+#[derive(Debug)]
+struct Timeline {
+    period: usize,
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl Timeline {
+    fn new(num_lines: usize, period: usize, width: usize) -> Self {
+        let words = (width + 63) / 64;
+        Timeline {
+            period,
+            words,
+            bits: vec![0; num_lines * period * words],
+        }
+    }
+
+    fn offset(&self, line: usize, minute: usize, i: usize) -> usize {
+        (line * self.period + minute % self.period) * self.words + i / 64
+    }
+
+    fn set(&mut self, line: usize, minute: usize, i: usize) {
+        let offset = self.offset(line, minute, i);
+        self.bits[offset] |= 1 << (i % 64);
+    }
+
+    fn get(&self, line: usize, minute: usize, i: usize) -> bool {
+        self.bits[self.offset(line, minute, i)] & (1 << (i % 64)) != 0
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseBasinError;
+
+/// Why no fastest route was found.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RouteError {
+    /// Blizzards keep the expedition from ever reaching some waypoint.
+    NoRoute,
+    Cancelled,
+}
+
+impl From<Cancelled> for RouteError {
+    fn from(_: Cancelled) -> RouteError {
+        RouteError::Cancelled
+    }
+}
+
+// Number of states expanded between two progress updates.
+const PROGRESS_INTERVAL: usize = 1024;
+
 #[derive(Debug)]
-struct Basin {
-    walls: HashSet<Position>,
-    blizzards: HashSet<(Position, Position)>,
+pub struct Basin {
     num_rows: i64,
     num_cols: i64,
     start: Position,
     goal: Position,
+    // Horizontal blizzards per row, repeating every `num_cols` minutes.
+    horizontal: Timeline,
+    // Vertical blizzards per column, repeating every `num_rows` minutes.
+    vertical: Timeline,
+    period: usize,
+    // Initial position and direction of every blizzard, kept for drawing.
+    blizzards: Vec<(Position, char)>,
 }
 
-impl Basin {
-    fn from_str(s: &str) -> Basin {
-        let mut walls: HashSet<Position> = HashSet::new();
-        let mut blizzards: HashSet<(Position, Position)> = HashSet::new();
-        let (mut num_rows, mut num_cols) = (i64::MIN, i64::MIN);
-        for (i, line) in s.lines().enumerate() {
+// A search state; blizzards repeat, so only the minute modulo the period of
+// the whole basin matters, along with the number of waypoints visited so far.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+struct State {
+    pos: Position,
+    phase: usize,
+    visited: usize,
+}
+
+impl FromStr for Basin {
+    type Err = ParseBasinError;
+
+    fn from_str(s: &str) -> Result<Basin, Self::Err> {
+        let lines: Vec<&str> = s.trim().lines().collect();
+        if lines.len() < 3 {
+            return Err(ParseBasinError);
+        }
+        let num_rows = lines.len() as i64 - 2;
+        let num_cols = lines[0].len() as i64 - 2;
+        if num_rows < 1 || num_cols < 1 {
+            return Err(ParseBasinError);
+        }
+        let (rows, cols) = (num_rows as usize, num_cols as usize);
+        let mut horizontal = Timeline::new(rows, cols, cols);
+        let mut vertical = Timeline::new(cols, rows, rows);
+        let mut blizzards = Vec::new();
+        for (i, line) in lines.iter().enumerate().skip(1).take(rows) {
             for (j, ch) in line.chars().enumerate() {
-                let (i, j) = (i as i64 - 1, j as i64 - 1);
-                let pos = Position::new(i, j);
-                num_rows = num_rows.max(i);
-                num_cols = num_cols.max(j);
+                let (r, c) = (i as i64 - 1, j as i64 - 1);
+                if "<>^v".contains(ch) {
+                    blizzards.push((Position::new(r, c), ch));
+                }
                 match ch {
-                    '#' => walls.insert(pos),
-                    '^' => blizzards.insert((pos, Position::new(-1, 0))),
-                    'v' => blizzards.insert((pos, Position::new(1, 0))),
-                    '<' => blizzards.insert((pos, Position::new(0, -1))),
-                    '>' => blizzards.insert((pos, Position::new(0, 1))),
-                    '.' => true,
-                    _ => unreachable!(),
-                };
+                    '#' | '.' => {}
+                    // Blizzards only ever move within the interior.
+                    '<' | '>' | '^' | 'v' if c < 0 || c >= num_cols => return Err(ParseBasinError),
+                    '<' | '>' => {
+                        let d = if ch == '<' { -1 } else { 1 };
+                        for t in 0..cols {
+                            let col = (c + d * t as i64).rem_euclid(num_cols) as usize;
+                            horizontal.set(r as usize, t, col);
+                        }
+                    }
+                    '^' | 'v' => {
+                        let d = if ch == '^' { -1 } else { 1 };
+                        for t in 0..rows {
+                            let row = (r + d * t as i64).rem_euclid(num_rows) as usize;
+                            vertical.set(c as usize, t, row);
+                        }
+                    }
+                    _ => return Err(ParseBasinError),
+                }
             }
         }
-        walls.insert(Position::new(-2, 0));
-        let start = Position::new(-1, 0);
-        let goal = Position::new(num_rows, num_cols - 1);
-        Basin {
-            walls,
-            blizzards,
+        Ok(Basin {
             num_rows,
             num_cols,
-            start,
-            goal,
+            start: Position::new(-1, 0),
+            goal: Position::new(num_rows, num_cols - 1),
+            horizontal,
+            vertical,
+            period: lcm(rows, cols),
+            blizzards,
+        })
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
+
+impl Basin {
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    pub fn goal(&self) -> Position {
+        self.goal
+    }
+
+    // Return whether the expedition may stand at the given position at the
+    // given minute.
+    fn is_free(&self, pos: &Position, minute: usize) -> bool {
+        if *pos == self.start || *pos == self.goal {
+            return true;
         }
+        if pos.row < 0 || pos.row >= self.num_rows || pos.col < 0 || pos.col >= self.num_cols {
+            return false;
+        }
+        let (row, col) = (pos.row as usize, pos.col as usize);
+        !self.horizontal.get(row, minute, col) && !self.vertical.get(col, minute, row)
     }
 
-    fn fewest_minutes_to_goal(&self, forgot_snacks: bool) -> i64 {
-        let mut minutes = 1;
-        let mut current = HashSet::from([self.start]);
-        let mut goals = if forgot_snacks {
-            vec![self.goal, self.start, self.goal]
-        } else {
-            vec![self.goal]
+    /// Find the fastest route from the start visiting each waypoint in order.
+    /// Returns `None` if some waypoint cannot be reached, or if the token is
+    /// cancelled before the search ends. The number of states expanded so far
+    /// is reported along with the most waypoints any of them visited.
+    pub fn fastest_route(
+        &self,
+        waypoints: &[Position],
+        token: &Token,
+        progress: &dyn Progress,
+    ) -> Option<Vec<Move>> {
+        // Distance still to cover after reaching the i-th waypoint.
+        let mut remaining = vec![0; waypoints.len() + 1];
+        for i in (1..waypoints.len()).rev() {
+            remaining[i - 1] = remaining[i] + waypoints[i - 1].manhattan_distance(&waypoints[i]);
+        }
+        let advance = |pos: Position, visited: usize| {
+            if waypoints.get(visited) == Some(&pos) {
+                visited + 1
+            } else {
+                visited
+            }
         };
-        loop {
-            // Brute force idea courtesy: u/KeyJ
-            let blizzards: HashSet<Position> = self
-                .blizzards
-                .iter()
-                .map(|(p, d)| {
-                    Position::new(
-                        (p.row + d.row * minutes).rem_euclid(self.num_rows),
-                        (p.col + d.col * minutes).rem_euclid(self.num_cols),
-                    )
+
+        let (mut expanded, mut most_visited) = (0, 0);
+        let (path, _) = astar(
+            &State {
+                pos: self.start,
+                phase: 0,
+                visited: advance(self.start, 0),
+            },
+            |state| {
+                if token.is_cancelled() {
+                    return Vec::new();
+                }
+                expanded += 1;
+                most_visited = most_visited.max(state.visited);
+                if expanded % PROGRESS_INTERVAL == 0 {
+                    progress.update("state", expanded, None, ("waypoints", most_visited));
+                }
+                let phase = (state.phase + 1) % self.period;
+                Move::ALL
+                    .iter()
+                    .map(|mv| state.pos + mv.offset())
+                    .filter(|pos| self.is_free(pos, phase))
+                    .map(|pos| {
+                        let visited = advance(pos, state.visited);
+                        (
+                            State {
+                                pos,
+                                phase,
+                                visited,
+                            },
+                            1,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |state| match waypoints.get(state.visited) {
+                Some(next) => state.pos.manhattan_distance(next) + remaining[state.visited],
+                None => 0,
+            },
+            |state| state.visited == waypoints.len(),
+        )?;
+        Some(
+            path.windows(2)
+                .map(|w| {
+                    let offset =
+                        Position::new(w[1].pos.row - w[0].pos.row, w[1].pos.col - w[0].pos.col);
+                    *Move::ALL.iter().find(|mv| mv.offset() == offset).unwrap()
                 })
-                .collect();
-            current = current
-                .iter()
-                .flat_map(|p| p.neighbors())
-                .filter(|p| !blizzards.contains(p) && !self.walls.contains(p))
-                .collect();
-            if current.contains(goals.last().unwrap()) {
-                current = HashSet::from([goals.pop().unwrap()]);
-            }
-            if goals.is_empty() {
-                return minutes;
-            }
+                .collect(),
+        )
+    }
 
-            minutes += 1;
+    // Draw the basin at the given minute with the expedition at the given
+    // position. Cells with more than one blizzard show how many there are.
+    fn draw(&self, minute: usize, expedition: Position) -> Vec<String> {
+        let mut blizzards: HashMap<Position, (char, usize)> = HashMap::new();
+        for &(pos, ch) in &self.blizzards {
+            let t = minute as i64;
+            let pos = match ch {
+                '<' => Position::new(pos.row, (pos.col - t).rem_euclid(self.num_cols)),
+                '>' => Position::new(pos.row, (pos.col + t).rem_euclid(self.num_cols)),
+                '^' => Position::new((pos.row - t).rem_euclid(self.num_rows), pos.col),
+                _ => Position::new((pos.row + t).rem_euclid(self.num_rows), pos.col),
+            };
+            blizzards.entry(pos).or_insert((ch, 0)).1 += 1;
         }
+        (-1..=self.num_rows)
+            .map(|row| {
+                (-1..=self.num_cols)
+                    .map(|col| {
+                        let pos = Position::new(row, col);
+                        if pos == expedition {
+                            'E'
+                        } else if pos == self.start || pos == self.goal {
+                            '.'
+                        } else if row < 0 || row == self.num_rows || col < 0 || col == self.num_cols
+                        {
+                            '#'
+                        } else {
+                            match blizzards.get(&pos) {
+                                None => '.',
+                                Some(&(ch, 1)) => ch,
+                                Some(&(_, n)) => char::from_digit(n.min(9) as u32, 10).unwrap(),
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
     }
+
+    fn waypoints(&self, forgot_snacks: bool) -> Vec<Position> {
+        if forgot_snacks {
+            vec![self.goal, self.start, self.goal]
+        } else {
+            vec![self.goal]
+        }
+    }
+
+    /// Return the fewest minutes to reach the goal, going back to the start
+    /// and to the goal again if the elves forgot their snacks.
+    pub fn fewest_minutes_to_goal(
+        &self,
+        forgot_snacks: bool,
+        token: &Token,
+        progress: &dyn Progress,
+    ) -> Result<i64, RouteError> {
+        let route = self.fastest_route(&self.waypoints(forgot_snacks), token, progress);
+        token.check()?;
+        route
+            .map(|route| route.len() as i64)
+            .ok_or(RouteError::NoRoute)
+    }
+}
+
+/// Parse the map of the basin.
+pub fn parse(s: &str) -> Result<Basin, ParseBasinError> {
+    s.parse()
 }
 
-pub fn fewest_minutes_to_goal_p1(s: &str) -> i64 {
-    let basin = Basin::from_str(s);
-    basin.fewest_minutes_to_goal(false)
+pub fn part1(basin: &Basin) -> i64 {
+    basin
+        .fewest_minutes_to_goal(false, &Token::default(), &NoProgress)
+        .unwrap()
 }
 
-pub fn fewest_minutes_to_goal_p2(s: &str) -> i64 {
-    let basin = Basin::from_str(s);
-    basin.fewest_minutes_to_goal(true)
+pub fn part2(basin: &Basin) -> i64 {
+    basin
+        .fewest_minutes_to_goal(true, &Token::default(), &NoProgress)
+        .unwrap()
+}
+
+/// Return a frame of the basin for every minute of the fastest route.
+pub fn frames(s: &str, forgot_snacks: bool) -> impl Iterator<Item = Frame> {
+    let basin = parse(s).unwrap_or_else(|e| panic!("failed to parse basin: {:?}", e));
+    let route = basin
+        .fastest_route(
+            &basin.waypoints(forgot_snacks),
+            &Token::default(),
+            &NoProgress,
+        )
+        .expect("no route through the basin");
+    let start = Frame {
+        caption: String::from("initial state"),
+        rows: basin.draw(0, basin.start),
+    };
+    let mut pos = basin.start;
+    let moves = route.into_iter().enumerate().map(move |(i, mv)| {
+        pos = pos + mv.offset();
+        Frame {
+            caption: format!("minute {}, {:?}", i + 1, mv).to_lowercase(),
+            rows: basin.draw(i + 1, pos),
+        }
+    });
+    iter::once(start).chain(moves)
 }
 
 #[cfg(test)]
@@ -138,12 +418,62 @@ mod tests {
 ######.#";
 
     #[test]
-    fn test_fewest_minutes_to_goal_p1() {
-        assert_eq!(fewest_minutes_to_goal_p1(INPUT), 18);
+    fn test_part1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), 18);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), 54);
+        // The two blizzards of this basin always fill its only column.
+        let blocked = parse("#.#\n#^#\n#v#\n#.#").unwrap();
+        assert_eq!(
+            blocked.fewest_minutes_to_goal(false, &Token::default(), &NoProgress),
+            Err(RouteError::NoRoute)
+        );
+    }
+
+    #[test]
+    fn test_fastest_route() {
+        let basin = parse(INPUT).unwrap();
+        let waypoints = [Position::new(2, 3), basin.goal(), Position::new(0, 0)];
+        let route = basin
+            .fastest_route(&waypoints, &Token::default(), &NoProgress)
+            .unwrap();
+
+        // Replay the route and check it never runs into a wall or blizzard.
+        let mut pos = basin.start();
+        let mut visited = Vec::new();
+        for (minute, mv) in route.iter().enumerate() {
+            pos = pos + mv.offset();
+            assert!(basin.is_free(&pos, minute + 1), "{:?} at {}", pos, minute);
+            if waypoints.get(visited.len()) == Some(&pos) {
+                visited.push(pos);
+            }
+        }
+        assert_eq!(visited, waypoints);
+        assert_eq!(pos, Position::new(0, 0));
+    }
+
+    #[test]
+    fn test_parse_basin() {
+        assert!("#.#\n#>#\n#.#".parse::<Basin>().is_ok());
+        assert!("#.##\n^..#\n##.#".parse::<Basin>().is_err());
+        assert!("#.##\n#..>\n##.#".parse::<Basin>().is_err());
+        assert!("#.##\n#..#v\n##.#".parse::<Basin>().is_err());
     }
 
     #[test]
-    fn test_fewest_minutes_to_goal_p2() {
-        assert_eq!(fewest_minutes_to_goal_p2(INPUT), 54);
+    fn test_frames() {
+        let frames: Vec<Frame> = frames(INPUT, false).collect();
+        assert_eq!(frames.len(), 19);
+        let initial = INPUT.replacen("#.", "#E", 1);
+        assert_eq!(frames[0].rows, initial.lines().collect::<Vec<_>>());
+        assert_eq!(frames[1].caption, "minute 1, down");
+        assert_eq!(
+            frames[1].rows,
+            vec!["#.######", "#E>3.<.#", "#<..<<.#", "#>2.22.#", "#>v..^<#", "######.#"]
+        );
+        assert_eq!(frames[18].rows[5], "######E#");
     }
 }
@@ -0,0 +1,434 @@
+use crate::visualize::Frame;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParsePaletteError;
+
+impl FromStr for Rgb {
+    type Err = ParsePaletteError;
+
+    fn from_str(s: &str) -> Result<Rgb, Self::Err> {
+        let s = s.strip_prefix('#').unwrap_or(s);
+        if s.len() != 6 {
+            return Err(ParsePaletteError);
+        }
+        let channel =
+            |i: usize| u8::from_str_radix(&s[i..i + 2], 16).map_err(|_| ParsePaletteError);
+        Ok(Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+/// The colour of each kind of tile.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Palette {
+    colours: HashMap<char, Rgb>,
+    background: Rgb,
+}
+
+impl Default for Palette {
+    /// Colours for the tiles the simulations draw.
+    fn default() -> Palette {
+        let colours = [
+            ('.', Rgb(15, 15, 35)),
+            ('#', Rgb(200, 200, 200)),
+            ('o', Rgb(230, 190, 80)),
+            ('+', Rgb(255, 120, 60)),
+            ('@', Rgb(230, 60, 60)),
+            ('E', Rgb(60, 230, 90)),
+            ('S', Rgb(60, 230, 90)),
+            ('B', Rgb(250, 240, 80)),
+        ]
+        .into_iter()
+        .chain("<>^v".chars().map(|ch| (ch, Rgb(120, 210, 255))))
+        .chain("23456789".chars().map(|ch| (ch, Rgb(40, 120, 220))))
+        .collect();
+        Palette {
+            colours,
+            background: Rgb(0, 0, 0),
+        }
+    }
+}
+
+impl FromStr for Palette {
+    type Err = ParsePaletteError;
+
+    /// Parse changes to the default palette such as `#=ffffff,.=000000`.
+    fn from_str(s: &str) -> Result<Palette, Self::Err> {
+        let mut palette = Palette::default();
+        for entry in s.split(',').filter(|entry| !entry.is_empty()) {
+            let (tile, colour) = entry.split_once('=').ok_or(ParsePaletteError)?;
+            let mut chars = tile.chars();
+            let (Some(tile), None) = (chars.next(), chars.next()) else {
+                return Err(ParsePaletteError);
+            };
+            palette = palette.with(tile, colour.parse()?);
+        }
+        Ok(palette)
+    }
+}
+
+impl Palette {
+    pub fn with(mut self, tile: char, colour: Rgb) -> Palette {
+        self.colours.insert(tile, colour);
+        self
+    }
+
+    pub fn colour(&self, tile: char) -> Rgb {
+        self.colours.get(&tile).copied().unwrap_or(self.background)
+    }
+}
+
+/// An RGB image.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// Draw every character of the rows as a square of `scale` pixels.
+    pub fn from_rows<S: AsRef<str>>(rows: &[S], palette: &Palette, scale: usize) -> Image {
+        let scale = scale.max(1);
+        let width = rows
+            .iter()
+            .map(|row| row.as_ref().chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut image = Image::new(width * scale, rows.len() * scale, palette.background);
+        for (r, row) in rows.iter().enumerate() {
+            for (c, tile) in row.as_ref().chars().enumerate() {
+                let colour = palette.colour(tile);
+                for y in r * scale..(r + 1) * scale {
+                    image.pixels[y * image.width + c * scale..][..scale].fill(colour);
+                }
+            }
+        }
+        image
+    }
+
+    pub fn from_frame(frame: &Frame, palette: &Palette, scale: usize) -> Image {
+        Image::from_rows(&frame.rows, palette, scale)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    // Return a copy grown to the given size, filled with the background.
+    fn padded(&self, width: usize, height: usize, background: Rgb) -> Image {
+        let mut image = Image::new(width, height, background);
+        for y in 0..self.height {
+            image.pixels[y * width..][..self.width]
+                .copy_from_slice(&self.pixels[y * self.width..][..self.width]);
+        }
+        image
+    }
+
+    /// Write the image as a binary PPM.
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let bytes: Vec<u8> = self.pixels.iter().flat_map(|p| [p.0, p.1, p.2]).collect();
+        writer.write_all(&bytes)
+    }
+
+    /// Write the image as a PNG. The image data is stored without
+    /// compression, which keeps the encoder small.
+    pub fn write_png<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flat_map(|p| [p.0, p.1, p.2]));
+        }
+
+        let mut header = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]);
+
+        writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+        write_png_chunk(&mut writer, b"IHDR", &header)?;
+        write_png_chunk(&mut writer, b"IDAT", &zlib_stored(&raw))?;
+        write_png_chunk(&mut writer, b"IEND", &[])
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+// Wrap the data in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(65535).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        out.extend((block.len() as u16).to_le_bytes());
+        out.extend((!(block.len() as u16)).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn write_png_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    let mut checked = kind.to_vec();
+    checked.extend(data);
+    writer.write_all(&checked)?;
+    writer.write_all(&crc32(&checked).to_be_bytes())
+}
+
+// Packs variable-width codes into bytes, least significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u32,
+    num_bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.current |= (code as u32) << self.num_bits;
+        self.num_bits += width;
+        while self.num_bits >= 8 {
+            self.bytes.push(self.current as u8);
+            self.current >>= 8;
+            self.num_bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.num_bits > 0 {
+            self.bytes.push(self.current as u8);
+        }
+        self.bytes
+    }
+}
+
+// Compress colour indices with the variable-width LZW variant used by GIF.
+fn lzw(indices: &[u8], min_code_size: u32) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut out = BitWriter {
+        bytes: Vec::new(),
+        current: 0,
+        num_bits: 0,
+    };
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut width = min_code_size + 1;
+    let mut next = end + 1;
+    out.write(clear, width);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        out.write(end, width);
+        return out.finish();
+    };
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = codes.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, width);
+        if next < 4096 {
+            if next == 1 << width {
+                width += 1;
+            }
+            codes.insert((prefix, index), next);
+            next += 1;
+        } else {
+            out.write(clear, width);
+            codes.clear();
+            width = min_code_size + 1;
+            next = end + 1;
+        }
+        prefix = index as u16;
+    }
+    out.write(prefix, width);
+    out.write(end, width);
+    out.finish()
+}
+
+/// Write the images as a looping animated GIF, showing each one for the given
+/// number of hundredths of a second. Smaller images are padded with the
+/// palette's background. Fails if the images use more than 256 colours.
+pub fn write_gif<W: Write>(
+    images: &[Image],
+    palette: &Palette,
+    delay: u16,
+    mut writer: W,
+) -> io::Result<()> {
+    let width = images.iter().map(|image| image.width).max().unwrap_or(0);
+    let height = images.iter().map(|image| image.height).max().unwrap_or(0);
+    let mut colours = vec![palette.background];
+    let mut indices: HashMap<Rgb, u8> = HashMap::from([(palette.background, 0)]);
+    for pixel in images.iter().flat_map(|image| &image.pixels) {
+        if !indices.contains_key(pixel) {
+            if colours.len() == 256 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "more than 256 colours",
+                ));
+            }
+            indices.insert(*pixel, colours.len() as u8);
+            colours.push(*pixel);
+        }
+    }
+    // The colour table holds 2^(bits) entries, at least 4.
+    let bits = (usize::BITS - (colours.len() - 1).leading_zeros()).max(2);
+    colours.resize(1 << bits, Rgb(0, 0, 0));
+
+    writer.write_all(b"GIF89a")?;
+    writer.write_all(&(width as u16).to_le_bytes())?;
+    writer.write_all(&(height as u16).to_le_bytes())?;
+    writer.write_all(&[0xf0 | (bits as u8 - 1), 0, 0])?;
+    let table: Vec<u8> = colours.iter().flat_map(|c| [c.0, c.1, c.2]).collect();
+    writer.write_all(&table)?;
+    writer.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+    for image in images {
+        let image = image.padded(width, height, palette.background);
+        writer.write_all(&[0x21, 0xf9, 0x04, 0x00])?;
+        writer.write_all(&delay.to_le_bytes())?;
+        writer.write_all(&[0, 0])?;
+        writer.write_all(&[0x2c, 0, 0, 0, 0])?;
+        writer.write_all(&(width as u16).to_le_bytes())?;
+        writer.write_all(&(height as u16).to_le_bytes())?;
+        writer.write_all(&[0, bits as u8])?;
+        let pixels: Vec<u8> = image.pixels.iter().map(|p| indices[p]).collect();
+        for block in lzw(&pixels, bits).chunks(255) {
+            writer.write_all(&[block.len() as u8])?;
+            writer.write_all(block)?;
+        }
+        writer.write_all(&[0])?;
+    }
+    writer.write_all(&[0x3b])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Decompress GIF LZW data, mirroring a decoder.
+    fn unlzw(bytes: &[u8], min_code_size: u32) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut width = min_code_size + 1;
+        let (mut bits, mut num_bits, mut pos) = (0u32, 0u32, 0);
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        loop {
+            while num_bits < width {
+                bits |= (bytes[pos] as u32) << num_bits;
+                pos += 1;
+                num_bits += 8;
+            }
+            let code = (bits & ((1 << width) - 1)) as usize;
+            bits >>= width;
+            num_bits -= width;
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.extend([vec![], vec![]]);
+                width = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(p)) => [p.clone(), vec![p[0]]].concat(),
+                (None, None) => panic!("bad code {}", code),
+            };
+            out.extend(&entry);
+            if let Some(p) = previous {
+                table.push([p, vec![entry[0]]].concat());
+                if table.len() == 1 << width && width < 12 {
+                    width += 1;
+                }
+            }
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw() {
+        // Pseudo-random data fills the code table, forcing a reset.
+        let data: Vec<u8> = (0..50000u64)
+            .scan(1u64, |x, _| {
+                *x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                Some((*x >> 60) as u8 % 4)
+            })
+            .collect();
+        assert_eq!(unlzw(&lzw(&data, 2), 2), data);
+        assert_eq!(unlzw(&lzw(&[], 2), 2), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_image() {
+        let palette: Palette = "#=ffffff,.=000000".parse().unwrap();
+        let image = Image::from_rows(&["#.", "."], &palette, 2);
+        assert_eq!((image.width(), image.height()), (4, 4));
+        assert_eq!(image.pixel(1, 1), Rgb(255, 255, 255));
+        assert_eq!(image.pixel(2, 1), Rgb(0, 0, 0));
+        assert_eq!("x=12345".parse::<Palette>(), Err(ParsePaletteError));
+
+        let mut ppm = Vec::new();
+        image.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR"));
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    }
+}
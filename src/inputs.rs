@@ -0,0 +1,118 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the directory inputs are read from.
+pub const DIR_VAR: &str = "AOC22_INPUT_DIR";
+
+/// Directory inputs are read from when neither a flag nor [`DIR_VAR`] says
+/// otherwise.
+pub const DEFAULT_DIR: &str = "input";
+
+/// Which input of a day to load.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Kind {
+    /// The personal puzzle input, `dayNN.txt`.
+    Puzzle,
+    /// The example from the puzzle description, `dayNN.example.txt`.
+    Example,
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Missing(PathBuf),
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Missing(path) => write!(f, "input file {} not found", path.display()),
+            Self::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Locates and loads puzzle inputs by day.
+#[derive(Debug, Clone)]
+pub struct Inputs {
+    dir: PathBuf,
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Inputs {
+        Inputs { dir: dir.into() }
+    }
+
+    /// Use the directory named by [`DIR_VAR`], falling back to [`DEFAULT_DIR`].
+    pub fn from_env() -> Inputs {
+        Inputs::new(env::var_os(DIR_VAR).unwrap_or_else(|| DEFAULT_DIR.into()))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Return the path of the given input, e.g. `input/day01.example.txt`.
+    pub fn path(&self, day: u8, kind: Kind) -> PathBuf {
+        match kind {
+            Kind::Puzzle => self.dir.join(format!("day{:02}.txt", day)),
+            Kind::Example => self.dir.join(format!("day{:02}.example.txt", day)),
+        }
+    }
+
+    /// Read the given input and [`normalize`] it.
+    pub fn load(&self, day: u8, kind: Kind) -> Result<String, InputError> {
+        let path = self.path(day, kind);
+        match fs::read_to_string(&path) {
+            Ok(s) => Ok(normalize(&s)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Err(InputError::Missing(path)),
+            Err(e) => Err(InputError::Io(path, e)),
+        }
+    }
+}
+
+/// Convert line endings to `\n`, strip trailing whitespace from every line and
+/// drop trailing blank lines, so that the result ends with a single newline.
+/// Leading whitespace is kept since some puzzles lay out their input in
+/// columns.
+pub fn normalize(s: &str) -> String {
+    let mut lines: Vec<&str> = s.lines().map(str::trim_end).collect();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    let mut normalized = lines.join("\n");
+    normalized.push('\n');
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("    [D]  \r\n[N] [C]\r\n\r\nmove 1\t\r\n\r\n\r\n"),
+            "    [D]\n[N] [C]\n\nmove 1\n"
+        );
+        assert_eq!(normalize("abc"), "abc\n");
+    }
+
+    #[test]
+    fn test_load() {
+        let inputs = Inputs::new("input");
+        assert_eq!(
+            inputs.path(1, Kind::Example),
+            Path::new("input/day01.example.txt")
+        );
+        let Err(InputError::Missing(path)) = inputs.load(19, Kind::Puzzle) else {
+            panic!("expected day 19 input to be missing");
+        };
+        assert_eq!(path, Path::new("input/day19.txt"));
+    }
+}
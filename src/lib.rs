@@ -1,3 +1,9 @@
+pub mod alloc;
+pub mod answer;
+pub mod baseline;
+pub mod cancel;
+#[cfg(feature = "fetch")]
+pub mod client;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -21,3 +27,15 @@ pub mod day21;
 pub mod day22;
 pub mod day23;
 pub mod day24;
+pub mod image;
+pub mod inputs;
+pub mod params;
+pub mod progress;
+pub mod runner;
+pub mod scaffold;
+pub mod solvers;
+#[cfg(feature = "fetch")]
+pub mod submit;
+pub mod trace;
+pub mod visualize;
+pub mod watch;
@@ -1,65 +1,881 @@
+use aoc22::alloc::Stats;
+use aoc22::image::{self, Image, Palette};
+use aoc22::inputs::{self, Inputs, Kind};
+use aoc22::params::{Assignment, Config, Params, ParseConfigError};
+use aoc22::progress::Bar;
+use aoc22::runner::{self, Outcome};
+use aoc22::solvers::{Context, Solver, SOLVERS};
+use aoc22::trace::{self, Filter, Level};
+use aoc22::visualize::{self, Player};
+use aoc22::watch::{self, Watcher};
+use aoc22::{baseline, scaffold, solvers};
+use std::collections::HashMap;
 use std::env;
+use std::fs;
 use std::io;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-fn main() {
-    let Some(command) = env::args().nth(1) else {
-        println!("Usage: aoc21 <dayNNx>");
+const USAGE: &str = "Usage: aoc22 [--input-dir DIR] [--example] [--config FILE] [--timeout SECS]
+             [--progress] [--param NAME=VALUE]... <dayNNx> [-]
+       aoc22 all [--input-dir DIR] [--example] [--config FILE] [--timeout SECS] [--progress]
+                 [--jobs N]
+       aoc22 fetch [--base-url URL] [--input-dir DIR] <dayNN>...
+       aoc22 submit [--base-url URL] [--input-dir DIR] [--log FILE] <dayNNx> [ANSWER]
+       aoc22 visualize [--input-dir DIR] [--example] [--delay MS] [--dump FILE] <dayNNx>
+       aoc22 image [--input-dir DIR] [--example] [--scale N] [--palette TILE=RRGGBB,...]
+                   [--every N] [--delay CS] <dayNNx> <FILE.ppm|FILE.png|FILE.gif>
+       aoc22 watch [--input-dir DIR] [--config FILE] [--timeout SECS] [--interval MS] <dayNN>
+       aoc22 new <dayNN>
+       aoc22 bench save [--criterion-dir DIR] [--baseline FILE]
+       aoc22 bench compare [--threshold PERCENT] [--criterion-dir DIR] [--baseline FILE]
+
+Every command also takes [--trace dayNN]... to print the debugging events of a day.";
+
+// Time limit of each solver when running all of them.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn usage() -> ! {
+    println!("{}", USAGE);
+    process::exit(1);
+}
+
+// Save or compare criterion results against the stored baseline.
+fn bench(args: &[String]) {
+    let Some(action) = args.first() else { usage() };
+    let mut criterion_dir = PathBuf::from("target/criterion");
+    let mut baseline_path = PathBuf::from("benches/baseline.json");
+    let mut threshold = 10.0;
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        let Some(value) = options.next() else { usage() };
+        match option.as_str() {
+            "--criterion-dir" => criterion_dir = PathBuf::from(value),
+            "--baseline" => baseline_path = PathBuf::from(value),
+            "--threshold" => {
+                let Ok(t) = value.parse() else { usage() };
+                threshold = t;
+            }
+            _ => usage(),
+        }
+    }
+
+    let current = baseline::read_criterion_estimates(&criterion_dir).unwrap_or_else(|e| {
+        println!("Failed to read {}: {}", criterion_dir.display(), e);
+        process::exit(2);
+    });
+    match action.as_str() {
+        "save" => {
+            if let Err(e) = baseline::save(&baseline_path, &current) {
+                println!("Failed to write {}: {}", baseline_path.display(), e);
+                process::exit(2);
+            }
+            println!(
+                "Saved {} estimates to {}",
+                current.len(),
+                baseline_path.display()
+            );
+        }
+        "compare" => {
+            let stored = baseline::load(&baseline_path).unwrap_or_else(|e| {
+                println!("Failed to read {}: {}", baseline_path.display(), e);
+                process::exit(2);
+            });
+            let comparison = baseline::compare(&stored, &current);
+            for change in &comparison.changes {
+                println!(
+                    "{:<50} {:>14.0} ns -> {:>14.0} ns ({:+.1}%)",
+                    change.id,
+                    change.baseline,
+                    change.current,
+                    change.percent()
+                );
+            }
+            for id in &comparison.new {
+                println!("{:<50} no baseline", id);
+            }
+            for id in &comparison.missing {
+                println!("{:<50} not run", id);
+            }
+            let regressions = comparison.regressions(threshold);
+            if !regressions.is_empty() {
+                println!(
+                    "{} benchmark(s) regressed by more than {}%:",
+                    regressions.len(),
+                    threshold
+                );
+                for change in &regressions {
+                    println!("  {} ({:+.1}%)", change.id, change.percent());
+                }
+            }
+            if !comparison.missing.is_empty() {
+                println!(
+                    "{} benchmark(s) in the baseline were not run",
+                    comparison.missing.len()
+                );
+            }
+            if !regressions.is_empty() || !comparison.missing.is_empty() {
+                process::exit(1);
+            }
+        }
+        _ => usage(),
+    }
+}
+
+// Parse a day such as `day01`.
+fn parse_day(s: &str) -> Option<u8> {
+    s.strip_prefix("day")?
+        .parse()
+        .ok()
+        .filter(|day| (1..=25).contains(day))
+}
+
+// Create a client for the given base URL with the session from the
+// environment.
+#[cfg(feature = "fetch")]
+fn client(base_url: &str) -> aoc22::client::Client {
+    let session = aoc22::client::session_from_env().unwrap_or_else(|e| {
+        println!("{}", e);
+        process::exit(2);
+    });
+    aoc22::client::Client::new(base_url, &session)
+}
+
+#[cfg(feature = "fetch")]
+fn default_base_url() -> String {
+    use aoc22::client::{BASE_URL_VAR, DEFAULT_BASE_URL};
+
+    env::var(BASE_URL_VAR).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string())
+}
+
+// Download the inputs of the given days that are not in the input directory yet.
+#[cfg(feature = "fetch")]
+fn fetch(args: &[String]) {
+    let mut inputs = Inputs::from_env();
+    let mut base_url = default_base_url();
+    let mut days = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base-url" => {
+                let Some(url) = args.next() else { usage() };
+                base_url = url.clone();
+            }
+            "--input-dir" => {
+                let Some(dir) = args.next() else { usage() };
+                inputs = Inputs::new(dir);
+            }
+            _ => {
+                let Some(day) = parse_day(arg) else { usage() };
+                days.push(day);
+            }
+        }
+    }
+    if days.is_empty() {
+        usage();
+    }
+
+    let missing: Vec<u8> = days
+        .into_iter()
+        .filter(|&day| !inputs.path(day, Kind::Puzzle).exists())
+        .collect();
+    if missing.is_empty() {
+        println!("All inputs already present in {}", inputs.dir().display());
+        return;
+    }
+    let mut client = client(&base_url);
+    for day in missing {
+        match client.fetch_input(&inputs, day) {
+            Ok(path) => println!("Saved day {} input to {}", day, path.display()),
+            Err(e) => {
+                println!("Failed to fetch day {}: {}", day, e);
+                process::exit(2);
+            }
+        }
+    }
+}
+
+// Submit an answer, by default the one the solver computes from the puzzle
+// input, unless the submission log already rules it out.
+#[cfg(feature = "fetch")]
+fn submit(args: &[String]) {
+    use aoc22::answer::Answer;
+    use aoc22::submit::{self, Log, Submission, DEFAULT_LOG, LOG_VAR};
+
+    let mut inputs = Inputs::from_env();
+    let mut base_url = default_base_url();
+    let mut log_path = PathBuf::from(env::var_os(LOG_VAR).unwrap_or_else(|| DEFAULT_LOG.into()));
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base-url" => {
+                let Some(url) = args.next() else { usage() };
+                base_url = url.clone();
+            }
+            "--input-dir" => {
+                let Some(dir) = args.next() else { usage() };
+                inputs = Inputs::new(dir);
+            }
+            "--log" => {
+                let Some(path) = args.next() else { usage() };
+                log_path = PathBuf::from(path);
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let (command, answer) = match positional[..] {
+        [command] => (command, None),
+        [command, answer] => (command, Some(answer.clone())),
+        _ => usage(),
+    };
+    let Some(solver) = solvers::find(command) else {
+        println!("{}: unknown command", command);
         process::exit(1);
     };
+    let answer = answer.map_or_else(
+        || {
+            let input = inputs.load(solver.day, Kind::Puzzle).unwrap_or_else(|e| {
+                println!("{}", e);
+                process::exit(2);
+            });
+            solver.run(&input).unwrap_or_else(|e| {
+                println!("Failed to parse day {} input: {}", solver.day, e);
+                process::exit(2);
+            })
+        },
+        Answer::from,
+    );
+    if answer.is_multiline() {
+        println!(
+            "{} has a multi-line answer, pass the answer to submit",
+            command
+        );
+        process::exit(1);
+    }
+    let answer = answer.to_string();
+
+    let mut log = Log::load(&log_path).unwrap_or_else(|e| {
+        println!("Failed to read {}: {}", log_path.display(), e);
+        process::exit(2);
+    });
+    let mut client = client(&base_url);
+    match submit::submit(&mut client, &mut log, solver.day, solver.part, &answer) {
+        Ok(Submission::Known(verdict)) => {
+            println!("Not submitting {}: already known to be {}", answer, verdict);
+        }
+        Ok(Submission::Sent(verdict)) => {
+            println!("{}: {}", answer, verdict);
+            if let Err(e) = log.save(&log_path) {
+                println!("Failed to write {}: {}", log_path.display(), e);
+                process::exit(2);
+            }
+        }
+        Err(e) => {
+            println!("Failed to submit {}: {}", answer, e);
+            process::exit(2);
+        }
+    }
+}
+
+#[cfg(not(feature = "fetch"))]
+fn fetch(_: &[String]) {
+    println!("fetch is unavailable: build with the `fetch` feature");
+    process::exit(1);
+}
+
+#[cfg(not(feature = "fetch"))]
+fn submit(_: &[String]) {
+    println!("submit is unavailable: build with the `fetch` feature");
+    process::exit(1);
+}
+
+// Generate and register the module of a new day.
+fn new(args: &[String]) {
+    let [day] = args else { usage() };
+    let Some(day) = parse_day(day) else { usage() };
+    match scaffold::create(Path::new("."), day) {
+        Ok(paths) => {
+            for path in paths {
+                println!("Wrote {}", path.display());
+            }
+        }
+        Err(e) => {
+            println!("Failed to create day {}: {}", day, e);
+            process::exit(2);
+        }
+    }
+}
 
-    let Ok(input) = io::read_to_string(io::stdin()) else {
-        println!("Failed to read input from stdin");
+// Read the parameters in the config file at the given path.
+fn load_config(path: &str) -> Config {
+    let config = fs::read_to_string(path).unwrap_or_else(|e| {
+        println!("Failed to read {}: {}", path, e);
         process::exit(2);
+    });
+    config.parse().unwrap_or_else(|e: ParseConfigError| {
+        println!("Invalid parameter in {} at line {}", path, e.line);
+        process::exit(2);
+    })
+}
+
+// Parse the argument of `--timeout`, in seconds, where 0 means no limit.
+fn parse_timeout(arg: Option<&String>) -> Option<Duration> {
+    let Some(secs) = arg.and_then(|arg| arg.parse().ok()) else {
+        usage()
     };
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
 
-    match command.as_ref() {
-        "day01a" => println!("{}", aoc22::day01::max_total_calories(&input)),
-        "day01b" => println!("{}", aoc22::day01::max3_total_calories(&input)),
-        "day02a" => println!("{}", aoc22::day02::total_score_p1(&input)),
-        "day02b" => println!("{}", aoc22::day02::total_score_p2(&input)),
-        "day03a" => println!("{}", aoc22::day03::sum_priorities_p1(&input)),
-        "day03b" => println!("{}", aoc22::day03::sum_priorities_p2(&input)),
-        "day04a" => println!("{}", aoc22::day04::num_fully_contained(&input)),
-        "day04b" => println!("{}", aoc22::day04::num_overlapping(&input)),
-        "day05a" => println!("{}", aoc22::day05::top_of_stack_p1(&input)),
-        "day05b" => println!("{}", aoc22::day05::top_of_stack_p2(&input)),
-        "day06a" => println!("{}", aoc22::day06::start_of_packet_p1(&input)),
-        "day06b" => println!("{}", aoc22::day06::start_of_packet_p2(&input)),
-        "day07a" => println!("{}", aoc22::day07::total_size_p1(&input)),
-        "day07b" => println!("{}", aoc22::day07::total_size_p2(&input)),
-        "day08a" => println!("{}", aoc22::day08::num_visible_trees(&input)),
-        "day08b" => println!("{}", aoc22::day08::highest_scenic_score(&input)),
-        "day09a" => println!("{}", aoc22::day09::num_tail_positions_p1(&input)),
-        "day09b" => println!("{}", aoc22::day09::num_tail_positions_p2(&input)),
-        "day10a" => println!("{}", aoc22::day10::sum_of_signal_strengths(&input)),
-        "day10b" => println!("{}", aoc22::day10::render_image(&input)),
-        "day11a" => println!("{}", aoc22::day11::monkey_business_level_p1(&input)),
-        "day11b" => println!("{}", aoc22::day11::monkey_business_level_p2(&input)),
-        "day12a" => println!("{}", aoc22::day12::num_steps_to_target_p1(&input)),
-        "day12b" => println!("{}", aoc22::day12::num_steps_to_target_p2(&input)),
-        "day13a" => println!("{}", aoc22::day13::sum_right_indices(&input)),
-        "day13b" => println!("{}", aoc22::day13::decoder_key(&input)),
-        "day14a" => println!("{}", aoc22::day14::num_resting_sand_units_p1(&input)),
-        "day14b" => println!("{}", aoc22::day14::num_resting_sand_units_p2(&input)),
-        "day15a" => println!("{}", aoc22::day15::num_empty_positions(&input, 2000000)),
-        "day15b" => println!(
-            "{}",
-            aoc22::day15::distress_beacon_tuning_frequency(&input, 4000000)
+// Describe how long a solver took and, if counted, what it allocated.
+fn report(parse_time: Duration, solve_time: Duration, memory: Option<Stats>) -> String {
+    let times = format!("parse: {:?}, solve: {:?}", parse_time, solve_time);
+    match memory {
+        Some(memory) => format!("{}, {}", times, memory),
+        None => times,
+    }
+}
+
+// Run a solver under the time limit, with a progress bar on stderr if asked.
+fn run_solver(
+    solver: &'static Solver,
+    input: String,
+    params: Params,
+    timeout: Option<Duration>,
+    show_progress: bool,
+) -> Outcome {
+    let mut context = Context::new(params);
+    let bar = show_progress.then(|| Arc::new(Bar::new(&solver.command())));
+    if let Some(bar) = &bar {
+        context.progress = bar.clone();
+    }
+    let outcome = runner::run(solver, input, context, timeout);
+    if let Some(bar) = bar {
+        bar.finish();
+    }
+    outcome
+}
+
+// Whether stdin is a terminal rather than piped or redirected input.
+#[cfg(unix)]
+fn stdin_is_terminal() -> bool {
+    unsafe { libc::isatty(libc::STDIN_FILENO) == 1 }
+}
+
+#[cfg(not(unix))]
+fn stdin_is_terminal() -> bool {
+    true
+}
+
+// Run a solver on its input from stdin when it is piped or given as `-`, or
+// else from the input directory, and report how long parsing and solving
+// took. Parameters are read from the config file first, then from the flags.
+fn solve(args: &[String]) {
+    let mut inputs = Inputs::from_env();
+    let mut kind = Kind::Puzzle;
+    let mut from_stdin = false;
+    let mut from_dir = false;
+    let mut config = Config::default();
+    let mut assignments: Vec<Assignment> = Vec::new();
+    let mut timeout = None;
+    let mut show_progress = false;
+    let mut command = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input-dir" => {
+                let Some(dir) = args.next() else { usage() };
+                inputs = Inputs::new(dir);
+                from_dir = true;
+            }
+            "--example" => {
+                kind = Kind::Example;
+                from_dir = true;
+            }
+            "--config" => {
+                let Some(path) = args.next() else { usage() };
+                config = load_config(path);
+            }
+            "--param" => {
+                let Some(assignment) = args.next() else {
+                    usage()
+                };
+                let Ok(assignment) = assignment.parse() else {
+                    println!("Invalid parameter {}, expected NAME=VALUE", assignment);
+                    process::exit(1);
+                };
+                assignments.push(assignment);
+            }
+            "--timeout" => timeout = parse_timeout(args.next()),
+            "--progress" => show_progress = true,
+            "-" => from_stdin = true,
+            _ if command.is_none() => command = Some(arg),
+            _ => usage(),
+        }
+    }
+    let Some(command) = command else { usage() };
+
+    let Some(solver) = solvers::find(command) else {
+        println!("{}: unknown command", command);
+        process::exit(1);
+    };
+
+    let mut from_config = config.assignments(&solver.module(), command, solver.params);
+    from_config.append(&mut assignments);
+    let params = Params::resolve(solver.params, &from_config).unwrap_or_else(|e| {
+        println!("{}: {}", command, e);
+        process::exit(1);
+    });
+
+    // Piped input is used unless a directory or example was asked for, and
+    // empty piped input, e.g. from /dev/null, falls back to the directory.
+    let mut piped = String::new();
+    if from_stdin || (!from_dir && !stdin_is_terminal()) {
+        let Ok(read) = io::read_to_string(io::stdin()) else {
+            println!("Failed to read input from stdin");
+            process::exit(2);
+        };
+        piped = read;
+    }
+    let input = if from_stdin || !piped.trim().is_empty() {
+        inputs::normalize(&piped)
+    } else {
+        inputs.load(solver.day, kind).unwrap_or_else(|e| {
+            println!("{}", e);
+            process::exit(2);
+        })
+    };
+
+    match run_solver(solver, input, params, timeout, show_progress) {
+        Outcome::Solved {
+            answer,
+            parse_time,
+            solve_time,
+            memory,
+            ..
+        } => {
+            println!("{}", answer);
+            // Report timings on stderr so the answer alone can be piped.
+            eprintln!("{}", report(parse_time, solve_time, memory));
+        }
+        Outcome::Invalid(e) => {
+            println!("Failed to parse day {} input: {}", solver.day, e);
+            process::exit(2);
+        }
+        Outcome::TimedOut(timeout) => {
+            println!("{} timed out after {} s", command, timeout.as_secs());
+            process::exit(2);
+        }
+        // The panic was already reported on stderr.
+        Outcome::Panicked(_) => process::exit(101),
+    }
+}
+
+// Run every solver on its input in order, each under the time limit, and
+// report its answer or why there is none.
+fn run_all(args: &[String]) {
+    let mut inputs = Inputs::from_env();
+    let mut kind = Kind::Puzzle;
+    let mut config = Config::default();
+    let mut timeout = Some(DEFAULT_TIMEOUT);
+    let mut show_progress = false;
+    let mut jobs = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input-dir" => {
+                let Some(dir) = args.next() else { usage() };
+                inputs = Inputs::new(dir);
+            }
+            "--example" => kind = Kind::Example,
+            "--config" => {
+                let Some(path) = args.next() else { usage() };
+                config = load_config(path);
+            }
+            "--timeout" => timeout = parse_timeout(args.next()),
+            "--progress" => show_progress = true,
+            "--jobs" => {
+                let Some(Ok(n)) = args.next().map(|n| n.parse()) else {
+                    usage()
+                };
+                jobs = n;
+            }
+            _ => usage(),
+        }
+    }
+
+    // Progress bars of parts running side by side would overwrite each other.
+    let show_progress = show_progress && jobs == 1;
+    let start = Instant::now();
+    // Stays `None` where CPU time is not measured, e.g. with `parallel`.
+    let mut cpu_time: Option<Duration> = None;
+    let solvers: Vec<&'static Solver> = SOLVERS.iter().collect();
+    runner::run_ordered(
+        &solvers,
+        jobs,
+        |&solver| {
+            let command = solver.command();
+            let (result, report, cpu_time) =
+                run_part(solver, &inputs, kind, &config, timeout, show_progress);
+            let line = match report {
+                Some(report) if result.contains('\n') => {
+                    format!("{}: ({})\n{}", command, report, result)
+                }
+                Some(report) => format!("{}: {} ({})", command, result, report),
+                None => format!("{}: {}", command, result),
+            };
+            (line, cpu_time)
+        },
+        |(line, time)| {
+            println!("{}", line);
+            if let Some(time) = time {
+                cpu_time = Some(cpu_time.unwrap_or_default() + time);
+            }
+        },
+    );
+    match cpu_time {
+        Some(cpu_time) => println!(
+            "total: {:?} (cpu time of solved parts: {:?})",
+            start.elapsed(),
+            cpu_time
         ),
-        "day16a" => println!("{}", aoc22::day16::max_released_pressure(&input)),
-        "day17a" => println!("{}", aoc22::day17::tower_height(&input)),
-        "day18a" => println!("{}", aoc22::day18::surface_area(&input)),
-        "day18b" => println!("{}", aoc22::day18::external_surface_area(&input)),
-        "day20a" => println!("{}", aoc22::day20::sum_grove_coordinates_p1(&input)),
-        "day20b" => println!("{}", aoc22::day20::sum_grove_coordinates_p2(&input)),
-        "day21a" => println!("{}", aoc22::day21::root_yells(&input)),
-        "day21b" => println!("{}", aoc22::day21::i_yell(&input)),
-        "day22a" => println!("{}", aoc22::day22::final_password(&input)),
-        "day23a" => println!("{}", aoc22::day23::empty_ground_tiles(&input)),
-        "day23b" => println!("{}", aoc22::day23::first_idle_round(&input)),
-        "day24a" => println!("{}", aoc22::day24::fewest_minutes_to_goal_p1(&input)),
-        "day24b" => println!("{}", aoc22::day24::fewest_minutes_to_goal_p2(&input)),
-        _ => println!("{}: unknown command", command),
+        None => println!("total: {:?}", start.elapsed()),
+    }
+}
+
+// Run a part for `all` or `watch`, and return its answer or error, along with
+// the report of its timings and its CPU time when solved.
+fn run_part(
+    solver: &'static Solver,
+    inputs: &Inputs,
+    kind: Kind,
+    config: &Config,
+    timeout: Option<Duration>,
+    show_progress: bool,
+) -> (String, Option<String>, Option<Duration>) {
+    let input = match inputs.load(solver.day, kind) {
+        Ok(input) => input,
+        Err(e) => return (e.to_string(), None, None),
+    };
+    let assignments = config.assignments(&solver.module(), &solver.command(), solver.params);
+    let params = match Params::resolve(solver.params, &assignments) {
+        Ok(params) => params,
+        Err(e) => return (e.to_string(), None, None),
+    };
+    match run_solver(solver, input, params, timeout, show_progress) {
+        Outcome::Solved {
+            answer,
+            parse_time,
+            solve_time,
+            memory,
+            cpu_time,
+        } => (
+            answer.to_string(),
+            Some(report(parse_time, solve_time, memory)),
+            cpu_time,
+        ),
+        Outcome::Invalid(e) => (format!("failed to parse input: {}", e), None, None),
+        Outcome::TimedOut(timeout) => (
+            format!("timed out after {} s", timeout.as_secs()),
+            None,
+            None,
+        ),
+        Outcome::Panicked(message) => {
+            let message = message.lines().next().unwrap_or_default();
+            (format!("panicked: {}", message), None, None)
+        }
+    }
+}
+
+// Run both parts of a day on its input and example, and the unit tests of the
+// day, whenever the inputs, the source of the day or the config file change,
+// and show how the answers and test results changed since the previous run.
+fn watch(args: &[String]) {
+    let mut inputs = Inputs::from_env();
+    let mut config_path = None;
+    let mut timeout = Some(DEFAULT_TIMEOUT);
+    let mut interval = Duration::from_millis(500);
+    let mut day = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input-dir" => {
+                let Some(dir) = args.next() else { usage() };
+                inputs = Inputs::new(dir);
+            }
+            "--config" => {
+                let Some(path) = args.next() else { usage() };
+                config_path = Some(path.clone());
+            }
+            "--timeout" => timeout = parse_timeout(args.next()),
+            "--interval" => {
+                let Some(Ok(ms)) = args.next().map(|ms| ms.parse()) else {
+                    usage()
+                };
+                interval = Duration::from_millis(ms);
+            }
+            _ if day.is_none() => day = Some(parse_day(arg).unwrap_or_else(|| usage())),
+            _ => usage(),
+        }
+    }
+    let Some(day) = day else { usage() };
+    let solvers: Vec<&'static Solver> = SOLVERS.iter().filter(|s| s.day == day).collect();
+    if solvers.is_empty() {
+        println!("No solver for day {}", day);
+        process::exit(2);
+    }
+
+    let mut paths = vec![
+        inputs.path(day, Kind::Puzzle),
+        inputs.path(day, Kind::Example),
+    ];
+    // The tests are built from the source, unlike the running solvers.
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    paths.push(crate_dir.join(format!("src/day{:02}.rs", day)));
+    paths.extend(config_path.iter().map(PathBuf::from));
+    let mut watcher = Watcher::new(paths);
+    let mut config = config_path.as_deref().map(load_config).unwrap_or_default();
+    let mut previous: HashMap<String, String> = HashMap::new();
+    loop {
+        for &solver in &solvers {
+            for kind in [Kind::Puzzle, Kind::Example] {
+                let mut label = solver.command();
+                if kind == Kind::Example {
+                    // Days without an example only run on the puzzle input.
+                    if !inputs.path(day, kind).exists() {
+                        continue;
+                    }
+                    label.push_str(" (example)");
+                }
+                let (result, _, _) = run_part(solver, &inputs, kind, &config, timeout, false);
+                println!(
+                    "{}",
+                    watch::diff(&label, &result, previous.get(&label).map(String::as_str))
+                );
+                previous.insert(label, result);
+            }
+        }
+        let label = format!("day{:02} tests", day);
+        let result = match watch::run_tests(crate_dir, day) {
+            Ok(results) => results.summary(),
+            Err(e) => format!("failed to build:\n{}", e),
+        };
+        println!(
+            "{}",
+            watch::diff(&label, &result, previous.get(&label).map(String::as_str))
+        );
+        previous.insert(label, result);
+
+        println!("Watching for changes, press Ctrl-C to stop.");
+        let changed = loop {
+            thread::sleep(interval);
+            let changed = watcher.changed();
+            if !changed.is_empty() {
+                break changed;
+            }
+        };
+        let changed: Vec<String> = changed.iter().map(|p| p.display().to_string()).collect();
+        println!("\n{} changed", changed.join(", "));
+        // Keep the last valid config while the file is being edited.
+        if let Some(path) = config_path.as_ref().filter(|&path| changed.contains(path)) {
+            match fs::read_to_string(path).map(|s| s.parse::<Config>()) {
+                Ok(Ok(reloaded)) => config = reloaded,
+                Ok(Err(e)) => println!("Invalid parameter in {} at line {}", path, e.line),
+                Err(e) => println!("Failed to read {}: {}", path, e),
+            }
+        }
+    }
+}
+
+// Animate the simulation solving a part in the terminal, or write its frames
+// to a file.
+fn visualize(args: &[String]) {
+    let mut inputs = Inputs::from_env();
+    let mut kind = Kind::Puzzle;
+    let mut delay = Duration::from_millis(100);
+    let mut dump = None;
+    let mut command = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input-dir" => {
+                let Some(dir) = args.next() else { usage() };
+                inputs = Inputs::new(dir);
+            }
+            "--example" => kind = Kind::Example,
+            "--delay" => {
+                let Some(Ok(ms)) = args.next().map(|ms| ms.parse()) else {
+                    usage()
+                };
+                delay = Duration::from_millis(ms);
+            }
+            "--dump" => {
+                let Some(path) = args.next() else { usage() };
+                dump = Some(PathBuf::from(path));
+            }
+            _ if command.is_none() => command = Some(arg),
+            _ => usage(),
+        }
+    }
+    let Some(command) = command else { usage() };
+    let Some(solver) = solvers::find(command) else {
+        println!("{}: unknown command", command);
+        process::exit(1);
+    };
+    let input = inputs.load(solver.day, kind).unwrap_or_else(|e| {
+        println!("{}", e);
+        process::exit(2);
+    });
+    let Some(frames) = visualize::frames(solver.day, solver.part, &input) else {
+        println!("{} has no visualisation", command);
+        process::exit(1);
+    };
+
+    let result = match &dump {
+        Some(path) => fs::File::create(path)
+            .and_then(|file| visualize::dump(frames, io::BufWriter::new(file)))
+            .map(|count| println!("Wrote {} frames to {}", count, path.display())),
+        None => Player::new(command, delay).play(frames),
+    };
+    if let Err(e) = result {
+        println!("Failed to visualize {}: {}", command, e);
+        process::exit(2);
+    }
+}
+
+// Export the grid state of a part as an image, or the whole simulation as an
+// animated GIF.
+fn export_image(args: &[String]) {
+    let mut inputs = Inputs::from_env();
+    let mut kind = Kind::Puzzle;
+    let mut scale = 4;
+    let mut palette = Palette::default();
+    let mut every = 1;
+    let mut delay = 10;
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input-dir" => {
+                let Some(dir) = args.next() else { usage() };
+                inputs = Inputs::new(dir);
+            }
+            "--example" => kind = Kind::Example,
+            "--scale" | "--every" | "--delay" => {
+                let Some(Ok(n)) = args.next().map(|n| n.parse::<u16>()) else {
+                    usage()
+                };
+                match arg.as_str() {
+                    "--scale" => scale = n.max(1) as usize,
+                    "--every" => every = n.max(1) as usize,
+                    _ => delay = n,
+                }
+            }
+            "--palette" => {
+                let Some(Ok(p)) = args.next().map(|p| p.parse()) else {
+                    usage()
+                };
+                palette = p;
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let [command, path] = positional[..] else {
+        usage()
+    };
+    let path = PathBuf::from(path);
+    let Some(solver) = solvers::find(command) else {
+        println!("{}: unknown command", command);
+        process::exit(1);
+    };
+    let input = inputs.load(solver.day, kind).unwrap_or_else(|e| {
+        println!("{}", e);
+        process::exit(2);
+    });
+    let Some(frames) = visualize::frames(solver.day, solver.part, &input) else {
+        println!("{} has no grid to export", command);
+        process::exit(1);
+    };
+
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let result = fs::File::create(&path).and_then(|file| {
+        let writer = io::BufWriter::new(file);
+        match extension {
+            "gif" => {
+                // Keep every n-th frame along with the last one.
+                let mut images = Vec::new();
+                let mut last = None;
+                for (i, frame) in frames.enumerate() {
+                    if i % every == 0 {
+                        images.push(Image::from_frame(&frame, &palette, scale));
+                        last = None;
+                    } else {
+                        last = Some(frame);
+                    }
+                }
+                images.extend(last.map(|frame| Image::from_frame(&frame, &palette, scale)));
+                image::write_gif(&images, &palette, delay, writer)
+            }
+            _ => {
+                let Some(frame) = frames.last() else {
+                    return Ok(());
+                };
+                let image = Image::from_frame(&frame, &palette, scale);
+                if extension == "png" {
+                    image.write_png(writer)
+                } else {
+                    image.write_ppm(writer)
+                }
+            }
+        }
+    });
+    match result {
+        Ok(()) => println!("Wrote {}", path.display()),
+        Err(e) => {
+            println!("Failed to write {}: {}", path.display(), e);
+            process::exit(2);
+        }
+    }
+}
+
+// Print the events enabled by the environment and by every `--trace` flag,
+// and return the remaining arguments.
+fn init_trace(mut args: Vec<String>) -> Vec<String> {
+    let Ok(mut filter) = Filter::from_env() else {
+        println!(
+            "Invalid {}, expected e.g. day16=debug,day17",
+            trace::FILTER_VAR
+        );
+        process::exit(1);
+    };
+    while let Some(i) = args.iter().position(|arg| arg == "--trace") {
+        if i + 1 == args.len() {
+            usage();
+        }
+        let target = args.remove(i + 1);
+        args.remove(i);
+        filter.enable(&target, Level::Trace);
+    }
+    trace::set_filter(filter);
+    args
+}
+
+fn main() {
+    let args = init_trace(env::args().skip(1).collect());
+    let Some(command) = args.first() else { usage() };
+
+    match command.as_str() {
+        "bench" => bench(&args[1..]),
+        "fetch" => fetch(&args[1..]),
+        "submit" => submit(&args[1..]),
+        "new" => new(&args[1..]),
+        "all" => run_all(&args[1..]),
+        "visualize" => visualize(&args[1..]),
+        "image" => export_image(&args[1..]),
+        "watch" => watch(&args[1..]),
+        _ => solve(&args),
     }
 }
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A constant of a puzzle that a solver lets the caller change, such as the
+/// row to look at on day 15, which differs between the example and the input.
+#[derive(Debug)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: i64,
+    /// Smallest value the solver accepts.
+    pub min: i64,
+}
+
+/// A value given to a parameter, written `name=value`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Assignment {
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseAssignmentError;
+
+impl FromStr for Assignment {
+    type Err = ParseAssignmentError;
+
+    fn from_str(s: &str) -> Result<Assignment, Self::Err> {
+        let (name, value) = s.split_once('=').ok_or(ParseAssignmentError)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ParseAssignmentError);
+        }
+        let value = value.trim().parse().map_err(|_| ParseAssignmentError)?;
+        Ok(Assignment {
+            name: name.to_string(),
+            value,
+        })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseConfigError {
+    /// One-based line number of the offending line.
+    pub line: usize,
+}
+
+/// Parameter values read from a config file, one `target.name = value` per
+/// line, where the target is either a day (`day15`) or a part (`day15a`).
+/// Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Config {
+    entries: Vec<(String, Assignment)>,
+}
+
+impl FromStr for Config {
+    type Err = ParseConfigError;
+
+    fn from_str(s: &str) -> Result<Config, Self::Err> {
+        let mut entries = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = || ParseConfigError { line: i + 1 };
+            let (target, assignment) = line.split_once('.').ok_or_else(error)?;
+            let assignment = assignment.parse().map_err(|_| error())?;
+            entries.push((target.trim().to_string(), assignment));
+        }
+        Ok(Config { entries })
+    }
+}
+
+impl Config {
+    /// Return the assignments for the given day and part, e.g. `day15` and
+    /// `day15a`, in file order. Assignments to the day are skipped for parts
+    /// without such a parameter.
+    pub fn assignments(&self, module: &str, command: &str, params: &[Param]) -> Vec<Assignment> {
+        let declared = |name: &String| params.iter().any(|p| p.name == name);
+        self.entries
+            .iter()
+            .filter(|(target, a)| target == command || (target == module && declared(&a.name)))
+            .map(|(_, assignment)| assignment.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParamError {
+    Unknown(String),
+    TooSmall { name: String, min: i64 },
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "unknown parameter {}", name),
+            Self::TooSmall { name, min } => write!(f, "{} must be at least {}", name, min),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// The value of every parameter of a solver.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Params {
+    values: HashMap<&'static str, i64>,
+}
+
+impl Params {
+    /// Return the defaults of the given parameters.
+    pub fn defaults(params: &[Param]) -> Params {
+        Params {
+            values: params.iter().map(|p| (p.name, p.default)).collect(),
+        }
+    }
+
+    /// Return the defaults of the given parameters overridden by the
+    /// assignments, later assignments taking precedence.
+    pub fn resolve(params: &[Param], assignments: &[Assignment]) -> Result<Params, ParamError> {
+        let mut resolved = Params::defaults(params);
+        for assignment in assignments {
+            let Some(param) = params.iter().find(|p| p.name == assignment.name) else {
+                return Err(ParamError::Unknown(assignment.name.clone()));
+            };
+            if assignment.value < param.min {
+                return Err(ParamError::TooSmall {
+                    name: assignment.name.clone(),
+                    min: param.min,
+                });
+            }
+            resolved.values.insert(param.name, assignment.value);
+        }
+        Ok(resolved)
+    }
+
+    /// Return the value of a declared parameter, converted to the type the
+    /// solver takes. Panics when the solver did not declare the parameter.
+    pub fn get<T: TryFrom<i64>>(&self, name: &str) -> T {
+        let value = *self
+            .values
+            .get(name)
+            .unwrap_or_else(|| panic!("undeclared parameter {}", name));
+        T::try_from(value).unwrap_or_else(|_| panic!("{} out of range: {}", name, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: &[Param] = &[
+        Param {
+            name: "row",
+            default: 2000000,
+            min: i64::MIN,
+        },
+        Param {
+            name: "rounds",
+            default: 20,
+            min: 0,
+        },
+    ];
+
+    #[test]
+    fn test_resolve() {
+        let row: Assignment = "row=10".parse().unwrap();
+        let params = Params::resolve(PARAMS, &[row]).unwrap();
+        assert_eq!(params.get::<i64>("row"), 10);
+        assert_eq!(params.get::<usize>("rounds"), 20);
+        assert_eq!(Params::resolve(PARAMS, &[]), Ok(Params::defaults(PARAMS)));
+
+        let unknown = "bound=20".parse().unwrap();
+        assert_eq!(
+            Params::resolve(PARAMS, &[unknown]),
+            Err(ParamError::Unknown(String::from("bound")))
+        );
+        let negative = "rounds=-1".parse().unwrap();
+        assert!(Params::resolve(PARAMS, &[negative]).is_err());
+        assert_eq!("row".parse::<Assignment>(), Err(ParseAssignmentError));
+        assert_eq!("=1".parse::<Assignment>(), Err(ParseAssignmentError));
+    }
+
+    #[test]
+    fn test_config() {
+        let config: Config = "# Example sizes
+day15.row = 10
+
+day15b.bound = 20
+day11a.rounds=1"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            config.assignments("day15", "day15a", PARAMS),
+            vec![Assignment {
+                name: String::from("row"),
+                value: 10
+            }]
+        );
+        assert_eq!(config.assignments("day15", "day15b", PARAMS).len(), 2);
+        assert_eq!(config.assignments("day15", "day15b", &[]).len(), 1);
+        assert_eq!(config.assignments("day11", "day11b", PARAMS), vec![]);
+        assert_eq!(
+            "day15.row = 10\nrow = 10".parse::<Config>(),
+            Err(ParseConfigError { line: 2 })
+        );
+    }
+}
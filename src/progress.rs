@@ -0,0 +1,122 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Receives updates from a long simulation.
+pub trait Progress: Send + Sync {
+    /// Report that `done` steps, such as rounds or minutes, out of `total` if
+    /// known are done, along with the size of the state worth watching, e.g.
+    /// `("frontier", 120)`.
+    fn update(&self, step: &str, done: usize, total: Option<usize>, state: (&str, usize));
+}
+
+/// Ignores every update.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn update(&self, _: &str, _: usize, _: Option<usize>, _: (&str, usize)) {}
+}
+
+// Time between two redraws of a bar.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+// Number of characters filled by a complete bar.
+const BAR_WIDTH: usize = 30;
+
+/// Return the line a progress bar with the given label shows for an update.
+pub fn render(
+    label: &str,
+    step: &str,
+    done: usize,
+    total: Option<usize>,
+    state: (&str, usize),
+) -> String {
+    let (name, size) = state;
+    match total {
+        Some(total) => {
+            let filled = (done.min(total) * BAR_WIDTH)
+                .checked_div(total)
+                .unwrap_or(BAR_WIDTH);
+            format!(
+                "{} [{}{}] {} {}/{}, {}: {}",
+                label,
+                "#".repeat(filled),
+                ".".repeat(BAR_WIDTH - filled),
+                step,
+                done,
+                total,
+                name,
+                size
+            )
+        }
+        None => format!("{} {} {}, {}: {}", label, step, done, name, size),
+    }
+}
+
+struct BarState {
+    drawn_at: Option<Instant>,
+    finished: bool,
+}
+
+/// Draws updates on a single line of stderr, at most ten times a second.
+pub struct Bar {
+    label: String,
+    state: Mutex<BarState>,
+}
+
+impl Bar {
+    pub fn new(label: &str) -> Bar {
+        Bar {
+            label: label.to_string(),
+            state: Mutex::new(BarState {
+                drawn_at: None,
+                finished: false,
+            }),
+        }
+    }
+
+    /// Erase the bar and ignore any later update, such as those of a solver
+    /// still running after its time is up.
+    pub fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.drawn_at.is_some() && !state.finished {
+            eprint!("\r\x1b[K");
+        }
+        state.finished = true;
+    }
+}
+
+impl Progress for Bar {
+    fn update(&self, step: &str, done: usize, total: Option<usize>, state: (&str, usize)) {
+        let mut bar = self.state.lock().unwrap();
+        let recent = matches!(bar.drawn_at, Some(at) if at.elapsed() < REDRAW_INTERVAL);
+        if bar.finished || recent {
+            return;
+        }
+        bar.drawn_at = Some(Instant::now());
+        eprint!("\r{}\x1b[K", render(&self.label, step, done, total, state));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("day11b", "round", 5000, Some(10000), ("inspections", 42)),
+            format!(
+                "day11b [{}{}] round 5000/10000, inspections: 42",
+                "#".repeat(15),
+                ".".repeat(15)
+            )
+        );
+        assert_eq!(
+            render("day23b", "round", 7, None, ("moves", 3)),
+            "day23b round 7, moves: 3"
+        );
+        assert!(
+            render("day20a", "number", 0, Some(0), ("round", 1)).contains(&"#".repeat(BAR_WIDTH))
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::alloc::{self, Stats};
+use crate::answer::Answer;
+use crate::cancel::Cancelled;
+use crate::solvers::{Context, Solver};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How running a solver ended.
+#[derive(Debug)]
+pub enum Outcome {
+    Solved {
+        answer: Answer,
+        parse_time: Duration,
+        solve_time: Duration,
+        /// What parsing and solving allocated, when allocations are counted.
+        memory: Option<Stats>,
+        /// CPU time spent parsing and solving, where the platform measures it.
+        cpu_time: Option<Duration>,
+    },
+    /// The input could not be parsed.
+    Invalid(String),
+    /// The solver did not finish within the time limit, so its token was
+    /// cancelled.
+    TimedOut(Duration),
+    /// The solver panicked with the given message.
+    Panicked(String),
+}
+
+// Return the CPU time used so far by the current thread. With the `parallel`
+// feature, solvers also run on rayon's threads, whose time would be missed, so
+// none is reported.
+#[cfg(all(unix, not(feature = "parallel")))]
+fn thread_cpu_time() -> Option<Duration> {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `time` is a valid timespec for the call to fill in.
+    let status = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) };
+    (status == 0).then(|| Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+#[cfg(any(not(unix), feature = "parallel"))]
+fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+// Return the message a thread panicked with.
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => String::from("unknown panic"),
+        },
+    }
+}
+
+/// Parse the input and solve the part on another thread, giving up after the
+/// timeout if any. The solver is then asked to stop through the token of the
+/// context, but solvers that never check it keep running in the background.
+pub fn run(
+    solver: &'static Solver,
+    input: String,
+    context: Context,
+    timeout: Option<Duration>,
+) -> Outcome {
+    let token = context.token.clone();
+    let (sender, receiver) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        let cpu_start = thread_cpu_time();
+        let (solved, memory) = alloc::measure(|| {
+            let start = Instant::now();
+            let parsed = (solver.parse)(&input).map_err(Outcome::Invalid)?;
+            let parse_time = start.elapsed();
+            let start = Instant::now();
+            let answer = (solver.solve)(&parsed, &context);
+            Ok((answer, parse_time, start.elapsed()))
+        });
+        let cpu_time = match (cpu_start, thread_cpu_time()) {
+            (Some(start), Some(end)) => Some(end.saturating_sub(start)),
+            _ => None,
+        };
+        let outcome = match solved {
+            Err(invalid) => invalid,
+            // A cancelled solver has nobody waiting for its outcome any more.
+            Ok((Err(Cancelled), _, _)) => return Ok(()),
+            Ok((Ok(answer), parse_time, solve_time)) => Outcome::Solved {
+                answer,
+                parse_time,
+                solve_time,
+                memory,
+                cpu_time,
+            },
+        };
+        sender.send(outcome)
+    });
+    let received = match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout),
+        None => receiver.recv().map_err(RecvTimeoutError::from),
+    };
+    match received {
+        Ok(outcome) => outcome,
+        Err(RecvTimeoutError::Timeout) => {
+            token.cancel();
+            Outcome::TimedOut(timeout.unwrap())
+        }
+        Err(RecvTimeoutError::Disconnected) => match handle.join() {
+            Ok(_) => unreachable!("solver finished without an outcome"),
+            Err(payload) => Outcome::Panicked(panic_message(payload)),
+        },
+    }
+}
+
+/// Call `f` on every item from `jobs` worker threads, and pass the results to
+/// `report` in the order of the items, each as soon as those before it are
+/// reported.
+pub fn run_ordered<I, T>(
+    items: &[I],
+    jobs: usize,
+    f: impl Fn(&I) -> T + Sync,
+    mut report: impl FnMut(T),
+) where
+    I: Sync,
+    T: Send,
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            let sender = sender.clone();
+            let (next, f) = (&next, &f);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else { break };
+                if sender.send((index, f(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        let mut reported = 0;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&reported) {
+                report(result);
+                reported += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::Params;
+    use crate::solvers;
+
+    #[test]
+    fn test_run() {
+        let solver = solvers::find("day01a").unwrap();
+        let context = Context::new(Params::default());
+        let outcome = run(solver, String::from("1\n2\n\n4\n"), context.clone(), None);
+        assert!(matches!(
+            outcome,
+            Outcome::Solved { answer, cpu_time, .. }
+                if answer == Answer::Signed(4) && cpu_time.is_some() == cfg!(all(unix, not(feature = "parallel")))
+        ));
+        let outcome = run(solver, String::from("x\n"), context, None);
+        assert!(matches!(outcome, Outcome::Invalid(_)));
+
+        // Far more rocks than could fall within the time limit.
+        let solver = solvers::find("day17a").unwrap();
+        let params = Params::resolve(solver.params, &["rocks=1000000000000".parse().unwrap()]);
+        let timeout = Duration::from_millis(50);
+        let context = Context::new(params.unwrap());
+        let outcome = run(solver, String::from("<>"), context, Some(timeout));
+        assert!(matches!(outcome, Outcome::TimedOut(t) if t == timeout));
+
+        // Later items finish first, but are reported in order.
+        let delays: Vec<u64> = vec![30, 20, 10, 0];
+        let mut reported = Vec::new();
+        run_ordered(
+            &delays,
+            4,
+            |&ms| {
+                thread::sleep(Duration::from_millis(ms));
+                ms
+            },
+            |ms| reported.push(ms),
+        );
+        assert_eq!(reported, delays);
+    }
+}
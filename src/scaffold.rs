@@ -0,0 +1,219 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum ScaffoldError {
+    /// The day already has a module.
+    Exists(PathBuf),
+    /// A file the new day is registered in is not laid out as expected.
+    Unrecognised(PathBuf),
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Exists(path) => write!(f, "{} already exists", path.display()),
+            Self::Unrecognised(path) => {
+                write!(f, "cannot find where to register in {}", path.display())
+            }
+            Self::Io(path, e) => write!(f, "failed to access {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for ScaffoldError {}
+
+/// Return the source of a new module for the given day.
+pub fn module_source(day: u8) -> String {
+    format!(
+        r#"#[derive(Debug, Eq, PartialEq)]
+pub struct ParseInputError;
+
+/// The puzzle input, parsed once for both parts.
+#[derive(Debug, Clone)]
+pub struct Input {{
+    lines: Vec<String>,
+}}
+
+/// Parse the puzzle input.
+pub fn parse(s: &str) -> Result<Input, ParseInputError> {{
+    let lines = s.lines().map(String::from).collect();
+    Ok(Input {{ lines }})
+}}
+
+pub fn part1(input: &Input) -> usize {{
+    let _lines = &input.lines;
+    todo!("solve day {day} part 1")
+}}
+
+pub fn part2(input: &Input) -> usize {{
+    let _lines = &input.lines;
+    todo!("solve day {day} part 2")
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    static INPUT: &str = "";
+
+    #[test]
+    #[ignore = "not solved yet"]
+    fn test_part1() {{
+        assert_eq!(part1(&parse(INPUT).unwrap()), 0);
+    }}
+
+    #[test]
+    #[ignore = "not solved yet"]
+    fn test_part2() {{
+        assert_eq!(part2(&parse(INPUT).unwrap()), 0);
+    }}
+}}
+"#
+    )
+}
+
+// Return the day of a line such as `pub mod day07;`.
+fn module_day(line: &str) -> Option<u8> {
+    line.strip_prefix("pub mod day")?
+        .strip_suffix(';')?
+        .parse()
+        .ok()
+}
+
+/// Add the module of the given day to the source of `lib.rs`, keeping the day
+/// modules in order.
+pub fn register_module(lib: &str, day: u8) -> Option<String> {
+    let lines: Vec<&str> = lib.lines().collect();
+    let days: Vec<(usize, u8)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| Some((i, module_day(line)?)))
+        .collect();
+    if days.iter().any(|&(_, d)| d == day) {
+        return None;
+    }
+    let at = match days.iter().find(|&&(_, d)| d > day) {
+        Some(&(i, _)) => i,
+        None => days.last()?.0 + 1,
+    };
+    let module = format!("pub mod day{:02};", day);
+    let mut lines = lines;
+    lines.insert(at, &module);
+    Some(lines.join("\n") + "\n")
+}
+
+/// Add solvers for both parts of the given day to the source of
+/// `solvers.rs`, keeping `SOLVERS` ordered by day.
+pub fn register_solvers(solvers: &str, day: u8) -> Option<String> {
+    let start = solvers.find("pub const SOLVERS: &[Solver] = &[\n")?;
+    let end = start + solvers[start..].find("\n];\n")? + 1;
+    let mut at = end;
+    let mut offset = start;
+    while let Some(i) = solvers[offset..end].find("    Solver {\n        day: ") {
+        let entry = offset + i;
+        let rest = &solvers[entry + "    Solver {\n        day: ".len()..];
+        let entry_day: u8 = rest[..rest.find(',')?].parse().ok()?;
+        if entry_day == day {
+            return None;
+        }
+        if entry_day > day {
+            at = entry;
+            break;
+        }
+        offset = entry + 1;
+    }
+    let entries: String = ['a', 'b']
+        .iter()
+        .zip(["part1", "part2"])
+        .map(|(part, name)| {
+            format!(
+                "    Solver {{\n        day: {day},\n        part: '{part}',\n        name: \"{name}\",\n        params: &[],\n        parse: |s| erase(day{day:02}::parse(s)),\n        solve: |input, _| Ok(day{day:02}::{name}(downcast(input)).into()),\n    }},\n"
+            )
+        })
+        .collect();
+    Some(format!("{}{}{}", &solvers[..at], entries, &solvers[at..]))
+}
+
+/// Create the module of the given day in the crate at `root`, register it in
+/// `lib.rs` and `solvers.rs` and create an empty example input. Return the
+/// paths of the created and changed files.
+pub fn create(root: &Path, day: u8) -> Result<Vec<PathBuf>, ScaffoldError> {
+    let module_path = root.join("src").join(format!("day{:02}.rs", day));
+    let lib_path = root.join("src").join("lib.rs");
+    let solvers_path = root.join("src").join("solvers.rs");
+    let example_path = root
+        .join("input")
+        .join(format!("day{:02}.example.txt", day));
+    if module_path.exists() {
+        return Err(ScaffoldError::Exists(module_path));
+    }
+
+    let read = |path: &Path| {
+        fs::read_to_string(path).map_err(|e| ScaffoldError::Io(path.to_path_buf(), e))
+    };
+    let lib = register_module(&read(&lib_path)?, day)
+        .ok_or_else(|| ScaffoldError::Unrecognised(lib_path.clone()))?;
+    let solvers = register_solvers(&read(&solvers_path)?, day)
+        .ok_or_else(|| ScaffoldError::Unrecognised(solvers_path.clone()))?;
+
+    let write = |path: &Path, contents: &str| {
+        fs::write(path, contents).map_err(|e| ScaffoldError::Io(path.to_path_buf(), e))
+    };
+    write(&module_path, &module_source(day))?;
+    write(&lib_path, &lib)?;
+    write(&solvers_path, &solvers)?;
+    let mut paths = vec![module_path, lib_path, solvers_path];
+    if !example_path.exists() {
+        fs::create_dir_all(root.join("input"))
+            .map_err(|e| ScaffoldError::Io(example_path.clone(), e))?;
+        write(&example_path, "")?;
+        paths.push(example_path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_module() {
+        let lib = "pub mod baseline;\npub mod day01;\npub mod day20;\npub mod inputs;\n";
+        assert_eq!(
+            register_module(lib, 19).unwrap(),
+            "pub mod baseline;\npub mod day01;\npub mod day19;\npub mod day20;\npub mod inputs;\n"
+        );
+        assert_eq!(
+            register_module(lib, 25).unwrap(),
+            "pub mod baseline;\npub mod day01;\npub mod day20;\npub mod day25;\npub mod inputs;\n"
+        );
+        assert!(register_module(lib, 20).is_none());
+    }
+
+    #[test]
+    fn test_register_solvers() {
+        let entry = |day| {
+            format!(
+                "    Solver {{\n        day: {day},\n        part: 'a',\n        name: \"f\",\n        params: &[],\n        parse: |s| erase(day{day:02}::parse(s)),\n        solve: |input, _| Ok(day{day:02}::f(downcast(input))),\n    }},\n"
+            )
+        };
+        let source = format!(
+            "pub const SOLVERS: &[Solver] = &[\n{}{}];\n",
+            entry(18),
+            entry(20)
+        );
+        let solvers = register_solvers(&source, 19).unwrap();
+        let day19 = solvers.find("        day: 19,").unwrap();
+        assert!(solvers.find("        day: 18,").unwrap() < day19);
+        assert!(day19 < solvers.find("        day: 20,").unwrap());
+        assert!(solvers.contains("solve: |input, _| Ok(day19::part2(downcast(input)).into()),"));
+        assert!(register_solvers(&source, 25)
+            .unwrap()
+            .ends_with("day25::part2(downcast(input)).into()),\n    },\n];\n"));
+        assert!(register_solvers(&source, 18).is_none());
+    }
+}
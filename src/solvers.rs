@@ -0,0 +1,620 @@
+use crate::answer::Answer;
+use crate::cancel::{Cancelled, Token};
+use crate::params::{Param, Params};
+use crate::progress::{NoProgress, Progress};
+use crate::*;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+/// The puzzle input of a day as returned by its `parse` function, with its
+/// type erased so that the solvers of every day fit in one table.
+pub type Parsed = Box<dyn Any>;
+
+/// A solver for one part of a day's puzzle.
+pub struct Solver {
+    pub day: u8,
+    /// Either 'a' or 'b'.
+    pub part: char,
+    /// Short description of the part, which its benchmark is named after.
+    pub name: &'static str,
+    /// Constants of the puzzle the caller may change.
+    pub params: &'static [Param],
+    /// Parse the puzzle input, the same way for both parts of the day.
+    pub parse: fn(&str) -> Result<Parsed, String>,
+    /// Solve the part from the input returned by `parse`.
+    pub solve: fn(&Parsed, &Context) -> Result<Answer, Cancelled>,
+}
+
+/// What a solver is given along with its input.
+#[derive(Clone)]
+pub struct Context {
+    /// A value for each parameter the solver declares.
+    pub params: Params,
+    /// Checked by the solvers of days 16, 17, 23 and 24, which may run long.
+    pub token: Token,
+    /// Updated by the simulations of days 11, 20, 23 and 24.
+    pub progress: Arc<dyn Progress>,
+}
+
+impl Context {
+    pub fn new(params: Params) -> Context {
+        Context {
+            params,
+            token: Token::default(),
+            progress: Arc::new(NoProgress),
+        }
+    }
+}
+
+impl Solver {
+    /// Return the command used to run this solver from the CLI, e.g. `day01a`.
+    pub fn command(&self) -> String {
+        format!("day{:02}{}", self.day, self.part)
+    }
+
+    /// Return the module of this solver, e.g. `day01`.
+    pub fn module(&self) -> String {
+        format!("day{:02}", self.day)
+    }
+
+    /// Return the default value of every parameter.
+    pub fn default_params(&self) -> Params {
+        Params::defaults(self.params)
+    }
+
+    /// Parse the input and solve the part with the default parameters.
+    pub fn run(&self, s: &str) -> Result<Answer, String> {
+        let context = Context::new(self.default_params());
+        (self.solve)(&(self.parse)(s)?, &context).map_err(|Cancelled| String::from("cancelled"))
+    }
+}
+
+// Erase the type of the input returned by the `parse` function of a day.
+fn erase<T: 'static, E: fmt::Debug>(parsed: Result<T, E>) -> Result<Parsed, String> {
+    match parsed {
+        Ok(input) => Ok(Box::new(input)),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+// Recover the input of a day from its erased form.
+fn downcast<T: 'static>(parsed: &Parsed) -> &T {
+    parsed
+        .downcast_ref()
+        .expect("input parsed by the solver of another day")
+}
+
+// Render an optional marker position from day 6.
+fn marker(position: Option<usize>) -> Result<Answer, Cancelled> {
+    Ok(position.map_or_else(|| Answer::from("no marker found"), Answer::from))
+}
+
+/// All solvers, ordered by day and part.
+pub const SOLVERS: &[Solver] = &[
+    Solver {
+        day: 1,
+        part: 'a',
+        name: "max_total_calories",
+        params: &[],
+        parse: |s| erase(day01::parse(s)),
+        solve: |input, _| Ok(day01::part1(downcast(input)).into()),
+    },
+    Solver {
+        day: 1,
+        part: 'b',
+        name: "max3_total_calories",
+        params: &[],
+        parse: |s| erase(day01::parse(s)),
+        solve: |input, _| Ok(day01::part2(downcast(input)).into()),
+    },
+    Solver {
+        day: 2,
+        part: 'a',
+        name: "total_score_p1",
+        params: &[],
+        parse: |s| erase(day02::parse(s)),
+        solve: |input, _| Ok(day02::part1(downcast(input)).into()),
+    },
+    Solver {
+        day: 2,
+        part: 'b',
+        name: "total_score_p2",
+        params: &[],
+        parse: |s| erase(day02::parse(s)),
+        solve: |input, _| Ok(day02::part2(downcast(input)).into()),
+    },
+    Solver {
+        day: 3,
+        part: 'a',
+        name: "sum_priorities_p1",
+        params: &[],
+        parse: |s| erase(day03::parse(s)),
+        solve: |input, _| Ok(day03::part1(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 3,
+        part: 'b',
+        name: "sum_priorities_p2",
+        params: &[],
+        parse: |s| erase(day03::parse(s)),
+        solve: |input, _| Ok(day03::part2(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 4,
+        part: 'a',
+        name: "num_fully_contained",
+        params: &[],
+        parse: |s| erase(day04::parse(s)),
+        solve: |input, _| Ok(day04::part1(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 4,
+        part: 'b',
+        name: "num_overlapping",
+        params: &[],
+        parse: |s| erase(day04::parse(s)),
+        solve: |input, _| Ok(day04::part2(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 5,
+        part: 'a',
+        name: "top_of_stack_p1",
+        params: &[],
+        parse: |s| erase(day05::parse(s)),
+        solve: |input, _| Ok(day05::part1(downcast(input)).into()),
+    },
+    Solver {
+        day: 5,
+        part: 'b',
+        name: "top_of_stack_p2",
+        params: &[],
+        parse: |s| erase(day05::parse(s)),
+        solve: |input, _| Ok(day05::part2(downcast(input)).into()),
+    },
+    Solver {
+        day: 6,
+        part: 'a',
+        name: "start_of_packet_p1",
+        params: &[],
+        parse: |s| erase(day06::parse(s)),
+        solve: |input, _| marker(day06::part1(downcast::<Vec<_>>(input))),
+    },
+    Solver {
+        day: 6,
+        part: 'b',
+        name: "start_of_packet_p2",
+        params: &[],
+        parse: |s| erase(day06::parse(s)),
+        solve: |input, _| marker(day06::part2(downcast::<Vec<_>>(input))),
+    },
+    Solver {
+        day: 7,
+        part: 'a',
+        name: "total_size_p1",
+        params: &[Param {
+            name: "max_size",
+            default: day07::MAX_SIZE as i64,
+            min: 0,
+        }],
+        parse: |s| erase(day07::parse(s)),
+        solve: |input, context| {
+            let max_size = context.params.get("max_size");
+            Ok(day07::total_size_at_most(downcast::<Vec<_>>(input), max_size).into())
+        },
+    },
+    Solver {
+        day: 7,
+        part: 'b',
+        name: "total_size_p2",
+        params: &[
+            Param {
+                name: "disk_size",
+                default: day07::DISK_SIZE as i64,
+                min: 0,
+            },
+            Param {
+                name: "required",
+                default: day07::REQUIRED as i64,
+                min: 0,
+            },
+        ],
+        parse: |s| erase(day07::parse(s)),
+        solve: |input, context| {
+            let (disk_size, required) = (
+                context.params.get("disk_size"),
+                context.params.get("required"),
+            );
+            Ok(day07::smallest_to_delete(downcast::<Vec<_>>(input), disk_size, required).into())
+        },
+    },
+    Solver {
+        day: 8,
+        part: 'a',
+        name: "num_visible_trees",
+        params: &[],
+        parse: |s| erase(day08::parse(s)),
+        solve: |input, _| Ok(day08::part1(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 8,
+        part: 'b',
+        name: "highest_scenic_score",
+        params: &[],
+        parse: |s| erase(day08::parse(s)),
+        solve: |input, _| Ok(day08::part2(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 9,
+        part: 'a',
+        name: "num_tail_positions_p1",
+        params: &[],
+        parse: |s| erase(day09::parse(s)),
+        solve: |input, _| Ok(day09::part1(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 9,
+        part: 'b',
+        name: "num_tail_positions_p2",
+        params: &[],
+        parse: |s| erase(day09::parse(s)),
+        solve: |input, _| Ok(day09::part2(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 10,
+        part: 'a',
+        name: "sum_of_signal_strengths",
+        params: &[],
+        parse: |s| erase(day10::parse(s)),
+        solve: |input, _| Ok(day10::part1(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 10,
+        part: 'b',
+        name: "render_image",
+        params: &[],
+        parse: |s| erase(day10::parse(s)),
+        solve: |input, _| Ok(day10::part2(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 11,
+        part: 'a',
+        name: "monkey_business_level_p1",
+        params: &[Param {
+            name: "rounds",
+            default: day11::ROUNDS_P1 as i64,
+            min: 0,
+        }],
+        parse: |s| erase(day11::parse(s)),
+        solve: |input, context| {
+            let rounds = context.params.get("rounds");
+            Ok(day11::monkey_business_level(
+                downcast::<Vec<_>>(input),
+                rounds,
+                true,
+                &*context.progress,
+            )
+            .into())
+        },
+    },
+    Solver {
+        day: 11,
+        part: 'b',
+        name: "monkey_business_level_p2",
+        params: &[Param {
+            name: "rounds",
+            default: day11::ROUNDS_P2 as i64,
+            min: 0,
+        }],
+        parse: |s| erase(day11::parse(s)),
+        solve: |input, context| {
+            let rounds = context.params.get("rounds");
+            Ok(day11::monkey_business_level(
+                downcast::<Vec<_>>(input),
+                rounds,
+                false,
+                &*context.progress,
+            )
+            .into())
+        },
+    },
+    Solver {
+        day: 12,
+        part: 'a',
+        name: "num_steps_to_target_p1",
+        params: &[],
+        parse: |s| erase(day12::parse(s)),
+        solve: |input, _| Ok(day12::part1(downcast(input)).into()),
+    },
+    Solver {
+        day: 12,
+        part: 'b',
+        name: "num_steps_to_target_p2",
+        params: &[],
+        parse: |s| erase(day12::parse(s)),
+        solve: |input, _| Ok(day12::part2(downcast(input)).into()),
+    },
+    Solver {
+        day: 13,
+        part: 'a',
+        name: "sum_right_indices",
+        params: &[],
+        parse: |s| erase(day13::parse(s)),
+        solve: |input, _| Ok(day13::part1(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 13,
+        part: 'b',
+        name: "decoder_key",
+        params: &[],
+        parse: |s| erase(day13::parse(s)),
+        solve: |input, _| Ok(day13::part2(downcast::<Vec<_>>(input)).into()),
+    },
+    Solver {
+        day: 14,
+        part: 'a',
+        name: "num_resting_sand_units_p1",
+        params: &[],
+        parse: |s| erase(day14::parse(s)),
+        solve: |input, _| Ok(day14::part1(downcast(input)).into()),
+    },
+    Solver {
+        day: 14,
+        part: 'b',
+        name: "num_resting_sand_units_p2",
+        params: &[],
+        parse: |s| erase(day14::parse(s)),
+        solve: |input, _| Ok(day14::part2(downcast(input)).into()),
+    },
+    Solver {
+        day: 15,
+        part: 'a',
+        name: "num_empty_positions",
+        params: &[Param {
+            name: "row",
+            default: day15::ROW,
+            min: i64::MIN,
+        }],
+        parse: |s| erase(day15::parse(s)),
+        solve: |input, context| {
+            let row = context.params.get("row");
+            Ok(day15::num_empty_positions(downcast::<Vec<_>>(input), row).into())
+        },
+    },
+    Solver {
+        day: 15,
+        part: 'b',
+        name: "distress_beacon_tuning_frequency",
+        params: &[Param {
+            name: "bound",
+            default: day15::BOUND,
+            min: 0,
+        }],
+        parse: |s| erase(day15::parse(s)),
+        solve: |input, context| {
+            let bound = context.params.get("bound");
+            Ok(day15::distress_beacon_tuning_frequency(downcast::<Vec<_>>(input), bound).into())
+        },
+    },
+    Solver {
+        day: 16,
+        part: 'a',
+        name: "max_released_pressure",
+        params: &[Param {
+            name: "minutes",
+            default: day16::MINUTES,
+            min: 0,
+        }],
+        parse: |s| erase(day16::parse(s)),
+        solve: |input, context| {
+            downcast::<day16::ValveTable>(input)
+                .max_released_pressure(context.params.get("minutes"), &context.token)
+                .map(Answer::from)
+        },
+    },
+    Solver {
+        day: 17,
+        part: 'a',
+        name: "tower_height",
+        params: &[
+            Param {
+                name: "rocks",
+                default: day17::NUM_ROCKS as i64,
+                min: 0,
+            },
+            Param {
+                name: "width",
+                default: day17::WIDTH as i64,
+                min: day17::MIN_WIDTH as i64,
+            },
+        ],
+        parse: |s| erase(day17::parse(s)),
+        solve: |input, context| {
+            let (num_rocks, width) = (context.params.get("rocks"), context.params.get("width"));
+            day17::tower_height(downcast::<Vec<_>>(input), num_rocks, width, &context.token)
+                .map(Answer::from)
+        },
+    },
+    Solver {
+        day: 18,
+        part: 'a',
+        name: "surface_area",
+        params: &[],
+        parse: |s| erase(day18::parse(s)),
+        solve: |input, _| Ok(day18::part1(downcast(input)).into()),
+    },
+    Solver {
+        day: 18,
+        part: 'b',
+        name: "external_surface_area",
+        params: &[],
+        parse: |s| erase(day18::parse(s)),
+        solve: |input, _| Ok(day18::part2(downcast(input)).into()),
+    },
+    Solver {
+        day: 20,
+        part: 'a',
+        name: "sum_grove_coordinates_p1",
+        params: &[
+            Param {
+                name: "key",
+                default: 1,
+                min: i64::MIN,
+            },
+            Param {
+                name: "rounds",
+                default: 1,
+                min: 0,
+            },
+        ],
+        parse: |s| erase(day20::parse(s)),
+        solve: |input, context| {
+            let (key, rounds) = (context.params.get("key"), context.params.get("rounds"));
+            Ok(day20::sum_grove_coordinates(
+                downcast::<Vec<_>>(input),
+                key,
+                rounds,
+                &*context.progress,
+            )
+            .into())
+        },
+    },
+    Solver {
+        day: 20,
+        part: 'b',
+        name: "sum_grove_coordinates_p2",
+        params: &[
+            Param {
+                name: "key",
+                default: day20::DECRYPTION_KEY,
+                min: i64::MIN,
+            },
+            Param {
+                name: "rounds",
+                default: day20::ROUNDS as i64,
+                min: 0,
+            },
+        ],
+        parse: |s| erase(day20::parse(s)),
+        solve: |input, context| {
+            let (key, rounds) = (context.params.get("key"), context.params.get("rounds"));
+            Ok(day20::sum_grove_coordinates(
+                downcast::<Vec<_>>(input),
+                key,
+                rounds,
+                &*context.progress,
+            )
+            .into())
+        },
+    },
+    Solver {
+        day: 21,
+        part: 'a',
+        name: "root_yells",
+        params: &[],
+        parse: |s| erase(day21::parse(s)),
+        solve: |input, _| Ok(day21::part1(downcast(input)).into()),
+    },
+    Solver {
+        day: 21,
+        part: 'b',
+        name: "i_yell",
+        params: &[],
+        parse: |s| erase(day21::parse(s)),
+        solve: |input, _| Ok(day21::part2(downcast(input)).into()),
+    },
+    Solver {
+        day: 22,
+        part: 'a',
+        name: "final_password",
+        params: &[],
+        parse: |s| erase(day22::parse(s)),
+        solve: |input, _| Ok(day22::part1(downcast(input)).into()),
+    },
+    Solver {
+        day: 23,
+        part: 'a',
+        name: "empty_ground_tiles",
+        params: &[],
+        parse: |s| erase(day23::parse(s)),
+        solve: |input, context| {
+            day23::empty_ground_tiles(downcast(input), &context.token, &*context.progress)
+                .map(Answer::from)
+        },
+    },
+    Solver {
+        day: 23,
+        part: 'b',
+        name: "first_idle_round",
+        params: &[],
+        parse: |s| erase(day23::parse(s)),
+        solve: |input, context| {
+            day23::first_idle_round(downcast(input), &context.token, &*context.progress)
+                .map(Answer::from)
+        },
+    },
+    Solver {
+        day: 24,
+        part: 'a',
+        name: "fewest_minutes_to_goal_p1",
+        params: &[],
+        parse: |s| erase(day24::parse(s)),
+        solve: |input, context| match downcast::<day24::Basin>(input).fewest_minutes_to_goal(
+            false,
+            &context.token,
+            &*context.progress,
+        ) {
+            Ok(minutes) => Ok(minutes.into()),
+            Err(day24::RouteError::Cancelled) => Err(Cancelled),
+            Err(day24::RouteError::NoRoute) => panic!("no route through the basin"),
+        },
+    },
+    Solver {
+        day: 24,
+        part: 'b',
+        name: "fewest_minutes_to_goal_p2",
+        params: &[],
+        parse: |s| erase(day24::parse(s)),
+        solve: |input, context| match downcast::<day24::Basin>(input).fewest_minutes_to_goal(
+            true,
+            &context.token,
+            &*context.progress,
+        ) {
+            Ok(minutes) => Ok(minutes.into()),
+            Err(day24::RouteError::Cancelled) => Err(Cancelled),
+            Err(day24::RouteError::NoRoute) => panic!("no route through the basin"),
+        },
+    },
+];
+
+/// Find the solver for the given CLI command, e.g. `day01a`.
+pub fn find(command: &str) -> Option<&'static Solver> {
+    SOLVERS.iter().find(|solver| solver.command() == command)
+}
+
+/// Return the distinct days that have at least one solver, in order.
+pub fn days() -> Vec<u8> {
+    let mut days: Vec<u8> = SOLVERS.iter().map(|solver| solver.day).collect();
+    days.dedup();
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find("day01b").unwrap().name, "max3_total_calories");
+        assert!(find("day26a").is_none());
+        assert!(days().windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            find("day01a").unwrap().run("1\n2\n\n4\n").unwrap(),
+            Answer::Signed(4)
+        );
+        assert!(find("day01a").unwrap().run("1\nx\n").is_err());
+        assert!(SOLVERS
+            .windows(2)
+            .all(|w| (w[0].day, w[0].part) < (w[1].day, w[1].part)));
+    }
+}
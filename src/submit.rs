@@ -0,0 +1,312 @@
+use crate::client::{Client, ClientError};
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable overriding [`DEFAULT_LOG`].
+pub const LOG_VAR: &str = "AOC22_SUBMISSION_LOG";
+
+/// File every submission attempt is recorded in.
+pub const DEFAULT_LOG: &str = "submissions.json";
+
+/// The server's judgement of a submitted answer.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Verdict {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// The answer was not checked because the last one was submitted too
+    /// recently. Holds the wait time the server reported, if any.
+    RateLimited(Option<String>),
+    /// The part was already solved or is not unlocked yet.
+    WrongLevel,
+    /// A response none of the above could be recognised in.
+    Unknown,
+}
+
+impl Verdict {
+    /// Recognise the verdict in the page returned after submitting an answer.
+    pub fn parse(page: &str) -> Verdict {
+        if page.contains("That's the right answer") {
+            Verdict::Right
+        } else if page.contains("You gave an answer too recently") {
+            let wait = page
+                .split_once("You have ")
+                .and_then(|(_, rest)| rest.split_once(" left to wait"))
+                .map(|(wait, _)| wait.to_string());
+            Verdict::RateLimited(wait)
+        } else if page.contains("You don't seem to be solving the right level") {
+            Verdict::WrongLevel
+        } else if page.contains("That's not the right answer") {
+            if page.contains("your answer is too high") {
+                Verdict::TooHigh
+            } else if page.contains("your answer is too low") {
+                Verdict::TooLow
+            } else {
+                Verdict::Wrong
+            }
+        } else {
+            Verdict::Unknown
+        }
+    }
+
+    /// Return whether the verdict rules the answer out.
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Verdict::Wrong | Verdict::TooHigh | Verdict::TooLow)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Verdict::Right => "right",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "too high",
+            Verdict::TooLow => "too low",
+            Verdict::RateLimited(_) => "rate limited",
+            Verdict::WrongLevel => "wrong level",
+            Verdict::Unknown => "unknown",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Verdict> {
+        Some(match name {
+            "right" => Verdict::Right,
+            "wrong" => Verdict::Wrong,
+            "too high" => Verdict::TooHigh,
+            "too low" => Verdict::TooLow,
+            "rate limited" => Verdict::RateLimited(None),
+            "wrong level" => Verdict::WrongLevel,
+            "unknown" => Verdict::Unknown,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::RateLimited(Some(wait)) => write!(f, "rate limited, {} left to wait", wait),
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+/// One submitted answer.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Attempt {
+    pub day: u8,
+    /// Either 'a' or 'b'.
+    pub part: char,
+    pub answer: String,
+    pub verdict: Verdict,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl Attempt {
+    fn to_json(&self) -> Value {
+        json!({
+            "day": self.day,
+            "part": self.part.to_string(),
+            "answer": self.answer,
+            "verdict": self.verdict.name(),
+            "timestamp": self.timestamp,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Attempt> {
+        Some(Attempt {
+            day: value["day"].as_u64()?.try_into().ok()?,
+            part: value["part"].as_str()?.chars().next()?,
+            answer: value["answer"].as_str()?.to_string(),
+            verdict: Verdict::from_name(value["verdict"].as_str()?)?,
+            timestamp: value["timestamp"].as_u64()?,
+        })
+    }
+}
+
+/// Every submission attempt, oldest first.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Log {
+    attempts: Vec<Attempt>,
+}
+
+impl Log {
+    /// Load the log from the given file, or start an empty one if it does not
+    /// exist yet.
+    pub fn load(path: &Path) -> io::Result<Log> {
+        let s = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Log::default()),
+            Err(e) => return Err(e),
+        };
+        let value: Value = serde_json::from_str(&s)?;
+        let attempts = value
+            .as_array()
+            .map(|attempts| attempts.iter().map(Attempt::from_json).collect())
+            .unwrap_or(None)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: malformed submission log", path.display()),
+                )
+            })?;
+        Ok(Log { attempts })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let attempts: Vec<Value> = self.attempts.iter().map(Attempt::to_json).collect();
+        fs::write(path, serde_json::to_string_pretty(&attempts)? + "\n")
+    }
+
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+
+    pub fn record(&mut self, attempt: Attempt) {
+        self.attempts.push(attempt);
+    }
+
+    /// Return the verdict the log already implies for the given answer: the
+    /// verdict of an earlier attempt with the same answer, the right answer
+    /// if the part was solved, or too high or too low when a numeric answer
+    /// lies beyond one already reported as such.
+    pub fn known_verdict(&self, day: u8, part: char, answer: &str) -> Option<Verdict> {
+        let attempts = self
+            .attempts
+            .iter()
+            .filter(|a| a.day == day && a.part == part);
+        let mut known = None;
+        for attempt in attempts {
+            let verdict = &attempt.verdict;
+            if attempt.answer == answer && (verdict.is_wrong() || *verdict == Verdict::Right) {
+                return Some(verdict.clone());
+            }
+            if *verdict == Verdict::Right {
+                known = Some(Verdict::Wrong);
+            }
+            if let (Ok(answer), Ok(previous)) =
+                (answer.parse::<i128>(), attempt.answer.parse::<i128>())
+            {
+                match verdict {
+                    Verdict::TooHigh if answer > previous => known = Some(Verdict::TooHigh),
+                    Verdict::TooLow if answer < previous => known = Some(Verdict::TooLow),
+                    _ => (),
+                }
+            }
+        }
+        known
+    }
+}
+
+/// The outcome of [`submit`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Submission {
+    /// The answer was posted and judged.
+    Sent(Verdict),
+    /// The answer was not posted since the log already judges it.
+    Known(Verdict),
+}
+
+/// Post the answer to the given part unless the log already judges it, and
+/// record the attempt.
+pub fn submit(
+    client: &mut Client,
+    log: &mut Log,
+    day: u8,
+    part: char,
+    answer: &str,
+) -> Result<Submission, ClientError> {
+    if let Some(verdict) = log.known_verdict(day, part, answer) {
+        return Ok(Submission::Known(verdict));
+    }
+    let level = if part == 'a' { "1" } else { "2" };
+    let page = client.post_form(
+        &format!("day/{}/answer", day),
+        &[("level", level), ("answer", answer)],
+    )?;
+    let verdict = Verdict::parse(&page);
+    log.record(Attempt {
+        day,
+        part,
+        answer: answer.to_string(),
+        verdict: verdict.clone(),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+    });
+    Ok(Submission::Sent(verdict))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::MockServer;
+
+    #[test]
+    fn test_parse_verdict() {
+        assert_eq!(
+            Verdict::parse("<p>That's the right answer! You are one gold star closer.</p>"),
+            Verdict::Right
+        );
+        assert_eq!(
+            Verdict::parse("<p>That's not the right answer; your answer is too low.</p>"),
+            Verdict::TooLow
+        );
+        assert_eq!(
+            Verdict::parse("<p>That's not the right answer. If you're stuck...</p>"),
+            Verdict::Wrong
+        );
+        assert_eq!(
+            Verdict::parse("<p>You gave an answer too recently. You have 42s left to wait.</p>"),
+            Verdict::RateLimited(Some(String::from("42s")))
+        );
+        assert_eq!(Verdict::parse("<html></html>"), Verdict::Unknown);
+    }
+
+    #[test]
+    fn test_known_verdict() {
+        let mut log = Log::default();
+        for (answer, verdict) in [("100", Verdict::TooHigh), ("10", Verdict::TooLow)] {
+            log.record(Attempt {
+                day: 1,
+                part: 'a',
+                answer: answer.to_string(),
+                verdict,
+                timestamp: 0,
+            });
+        }
+        assert_eq!(log.known_verdict(1, 'a', "100"), Some(Verdict::TooHigh));
+        assert_eq!(log.known_verdict(1, 'a', "150"), Some(Verdict::TooHigh));
+        assert_eq!(log.known_verdict(1, 'a', "5"), Some(Verdict::TooLow));
+        assert_eq!(log.known_verdict(1, 'a', "50"), None);
+        assert_eq!(log.known_verdict(1, 'b', "100"), None);
+    }
+
+    #[test]
+    fn test_submit() {
+        let server = MockServer::start(200, "<p>That's not the right answer.</p>");
+        let mut client = Client::new(&server.url, "abc123");
+        let mut log = Log::default();
+        assert_eq!(
+            submit(&mut client, &mut log, 3, 'b', "42").unwrap(),
+            Submission::Sent(Verdict::Wrong)
+        );
+        assert_eq!(
+            submit(&mut client, &mut log, 3, 'b', "42").unwrap(),
+            Submission::Known(Verdict::Wrong)
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("POST /2022/day/3/answer "));
+        assert!(requests[0].ends_with("level=2&answer=42"));
+
+        let path = std::env::temp_dir().join(format!("aoc22-log-{}.json", std::process::id()));
+        log.save(&path).unwrap();
+        assert_eq!(Log::load(&path).unwrap(), log);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,180 @@
+use lazy_static::lazy_static;
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+/// Environment variable holding the filter read by [`Filter::from_env`].
+pub const FILTER_VAR: &str = "AOC22_LOG";
+
+/// How much detail an event gives, from least to most.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseFilterError;
+
+impl FromStr for Level {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Level, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(ParseFilterError),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        })
+    }
+}
+
+/// Which events to print, written as comma-separated directives such as
+/// `day16=debug,day17`. A directive without a level enables every event of its
+/// target, and one without a target sets the level of every target.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Filter {
+    default: Option<Level>,
+    targets: Vec<(String, Level)>,
+}
+
+impl FromStr for Filter {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Filter, Self::Err> {
+        let mut filter = Filter::default();
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => filter.enable(target, level.parse()?),
+                None => match directive.parse() {
+                    Ok(level) => filter.default = Some(level),
+                    Err(_) => filter.enable(directive, Level::Trace),
+                },
+            }
+        }
+        Ok(filter)
+    }
+}
+
+impl Filter {
+    /// Read the filter in [`FILTER_VAR`], printing nothing if it is unset.
+    pub fn from_env() -> Result<Filter, ParseFilterError> {
+        match env::var(FILTER_VAR) {
+            Ok(s) => s.parse(),
+            Err(_) => Ok(Filter::default()),
+        }
+    }
+
+    /// Print the events of the target up to the given level.
+    pub fn enable(&mut self, target: &str, level: Level) {
+        self.targets.retain(|(t, _)| t != target);
+        self.targets.push((target.to_string(), level));
+    }
+
+    /// Return whether events of the given target and level are printed.
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        let max = self
+            .targets
+            .iter()
+            .find(|(t, _)| t == target)
+            .map(|&(_, level)| level)
+            .or(self.default);
+        matches!(max, Some(max) if level <= max)
+    }
+
+    // Return the most detailed level of any target.
+    fn max_level(&self) -> Option<Level> {
+        self.targets
+            .iter()
+            .map(|&(_, level)| level)
+            .chain(self.default)
+            .max()
+    }
+}
+
+lazy_static! {
+    static ref FILTER: RwLock<Filter> = RwLock::new(Filter::default());
+}
+
+// The most detailed level enabled for any target, or 0 when none is, so that
+// disabled events cost a single load.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// Replace the filter deciding which events are printed.
+pub fn set_filter(filter: Filter) {
+    let max = filter.max_level().map_or(0, |level| level as u8);
+    *FILTER.write().unwrap() = filter;
+    MAX_LEVEL.store(max, Ordering::Relaxed);
+}
+
+/// Return whether events of the given target and level are printed.
+pub fn enabled(target: &str, level: Level) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+        && FILTER.read().unwrap().enabled(target, level)
+}
+
+/// Print an event on stderr. Use [`event!`](crate::event) instead, which
+/// only formats the event when it is enabled.
+pub fn emit(target: &str, level: Level, args: fmt::Arguments) {
+    eprintln!("[{} {}] {}", target, level, args);
+}
+
+/// Return the target of events from the given module path, e.g. `day17` for
+/// `aoc22::day17`.
+pub fn target(module_path: &str) -> &str {
+    module_path.rsplit("::").next().unwrap_or(module_path)
+}
+
+/// Print an event of the given level, targeted at the calling module, when the
+/// filter enables it.
+#[macro_export]
+macro_rules! event {
+    ($level:expr, $($arg:tt)+) => {{
+        let target = $crate::trace::target(module_path!());
+        if $crate::trace::enabled(target, $level) {
+            $crate::trace::emit(target, $level, format_args!($($arg)+));
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let filter: Filter = "info, day16=debug,day17".parse().unwrap();
+        assert!(filter.enabled("day16", Level::Debug));
+        assert!(!filter.enabled("day16", Level::Trace));
+        assert!(filter.enabled("day17", Level::Trace));
+        assert!(filter.enabled("day23", Level::Info));
+        assert!(!filter.enabled("day23", Level::Debug));
+        assert_eq!(filter.max_level(), Some(Level::Trace));
+
+        let mut filter = Filter::default();
+        assert!(!filter.enabled("day16", Level::Error));
+        filter.enable("day16", Level::Debug);
+        assert!(filter.enabled("day16", Level::Debug));
+        assert_eq!("day16=loud".parse::<Filter>(), Err(ParseFilterError));
+        assert_eq!(target("aoc22::day17"), "day17");
+    }
+}
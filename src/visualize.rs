@@ -0,0 +1,305 @@
+use crate::{day10, day14, day15, day17, day23, day24};
+use std::io::{self, Read, Write};
+use std::iter;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// One step of a simulation drawn as text.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Frame {
+    pub caption: String,
+    pub rows: Vec<String>,
+}
+
+pub type Frames = Box<dyn Iterator<Item = Frame>>;
+
+/// Return the frames of the simulation solving the given part, if the day has
+/// one. Days with a single grid state to show have a single frame.
+pub fn frames(day: u8, part: char, input: &str) -> Option<Frames> {
+    let still = |caption: &str, rows: Vec<String>| -> Frames {
+        Box::new(iter::once(Frame {
+            caption: caption.to_string(),
+            rows,
+        }))
+    };
+    let frames: Frames = match (day, part) {
+        (10, _) => {
+            let program =
+                day10::parse(input).unwrap_or_else(|e| panic!("failed to parse program: {:?}", e));
+            let crt = day10::part2(&program);
+            still("CRT", crt.lines().map(String::from).collect())
+        }
+        (15, _) => still("sensor coverage", day15::coverage(input, 400)),
+        (14, 'a') => Box::new(day14::frames(input, false)),
+        (14, 'b') => Box::new(day14::frames(input, true)),
+        (17, 'a') => Box::new(day17::frames(input)),
+        (23, 'a') => Box::new(day23::frames(input, Some(10))),
+        (23, 'b') => Box::new(day23::frames(input, None)),
+        (24, 'a') => Box::new(day24::frames(input, false)),
+        (24, 'b') => Box::new(day24::frames(input, true)),
+        _ => return None,
+    };
+    Some(frames)
+}
+
+/// Write every frame as plain text, each preceded by its caption.
+pub fn dump<W: Write>(frames: Frames, mut writer: W) -> io::Result<usize> {
+    let mut count = 0;
+    for frame in frames {
+        writeln!(writer, "== {} ==", frame.caption)?;
+        for row in &frame.rows {
+            writeln!(writer, "{}", row)?;
+        }
+        writeln!(writer)?;
+        count += 1;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+// Return the ANSI escape code colouring the given cell, if any.
+fn colour(ch: char) -> Option<&'static str> {
+    match ch {
+        '#' => Some("\x1b[90m"),
+        'o' | '+' => Some("\x1b[33m"),
+        '@' => Some("\x1b[31m"),
+        'E' => Some("\x1b[1;32m"),
+        '<' | '>' | '^' | 'v' | '2'..='9' => Some("\x1b[36m"),
+        _ => None,
+    }
+}
+
+/// Render a row with ANSI colours, cut to the given width.
+pub fn colourize(row: &str, width: usize) -> String {
+    let mut s = String::new();
+    let mut current = None;
+    for ch in row.chars().take(width) {
+        let code = colour(ch);
+        if code != current {
+            s.push_str(code.unwrap_or("\x1b[0m"));
+            current = code;
+        }
+        s.push(ch);
+    }
+    if current.is_some() {
+        s.push_str("\x1b[0m");
+    }
+    s
+}
+
+// Run stty on the controlling terminal and return its output.
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Return the number of rows and columns of the terminal.
+fn terminal_size() -> (usize, usize) {
+    stty(&["size"])
+        .and_then(|size| {
+            let (rows, cols) = size.split_once(' ')?;
+            Some((rows.parse().ok()?, cols.parse().ok()?))
+        })
+        .unwrap_or((40, 120))
+}
+
+// Puts the terminal in cbreak mode for as long as it lives, so keys arrive
+// without waiting for enter and are not echoed.
+struct RawMode {
+    saved: Option<String>,
+}
+
+impl RawMode {
+    fn enable() -> RawMode {
+        let saved = stty(&["-g"]);
+        if saved.is_some() {
+            stty(&["-icanon", "-echo", "min", "1"]);
+        }
+        RawMode { saved }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            stty(&[saved]);
+        }
+        print!("\x1b[?25h");
+        let _ = io::stdout().flush();
+    }
+}
+
+// Forward keys pressed on stdin to the returned channel.
+fn keys() -> Receiver<u8> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut key = [0];
+        while let Ok(1) = stdin.read(&mut key) {
+            if sender.send(key[0]).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Plays frames in the terminal. Space pauses and resumes, `n` steps one frame
+/// while paused, `+` and `-` change the speed and `q` quits. Once stdin ends,
+/// the frames play to the end.
+pub struct Player {
+    title: String,
+    delay: Duration,
+    stride: usize,
+    // Rows and columns of the terminal.
+    size: (usize, usize),
+}
+
+const MIN_DELAY: Duration = Duration::from_millis(1);
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+impl Player {
+    pub fn new(title: &str, delay: Duration) -> Player {
+        Player {
+            title: title.to_string(),
+            delay: delay.clamp(MIN_DELAY, MAX_DELAY),
+            stride: 1,
+            size: (40, 120),
+        }
+    }
+
+    // Speed up by shortening the delay, then by skipping frames.
+    fn faster(&mut self) {
+        if self.delay > MIN_DELAY {
+            self.delay = (self.delay / 2).max(MIN_DELAY);
+        } else {
+            self.stride *= 2;
+        }
+    }
+
+    fn slower(&mut self) {
+        if self.stride > 1 {
+            self.stride /= 2;
+        } else {
+            self.delay = (self.delay * 2).min(MAX_DELAY);
+        }
+    }
+
+    fn draw(&self, frame: &Frame, index: usize, paused: bool) -> io::Result<()> {
+        let (height, width) = self.size;
+        let mut out = io::stdout().lock();
+        write!(out, "\x1b[H\x1b[2J")?;
+        writeln!(
+            out,
+            "{} | frame {} | {}",
+            self.title,
+            index + 1,
+            frame.caption
+        )?;
+        for row in frame.rows.iter().take(height.saturating_sub(3)) {
+            writeln!(out, "{}", colourize(row, width))?;
+        }
+        write!(
+            out,
+            "{} | {} ms x{} | space: pause  n: step  +/-: speed  q: quit",
+            if paused { "paused" } else { "playing" },
+            self.delay.as_millis(),
+            self.stride
+        )?;
+        out.flush()
+    }
+
+    /// Play the frames until they run out or the user quits.
+    pub fn play(mut self, frames: Frames) -> io::Result<()> {
+        let _raw = RawMode::enable();
+        self.size = terminal_size();
+        let keys = keys();
+        print!("\x1b[?25l");
+        let mut frames = frames.enumerate();
+        let Some(mut current) = frames.next() else {
+            return Ok(());
+        };
+        let mut paused = false;
+        loop {
+            self.draw(&current.1, current.0, paused)?;
+            let key = if paused {
+                match keys.recv() {
+                    Ok(key) => Some(key),
+                    // Without more keys to unpause it, play the rest.
+                    Err(_) => {
+                        paused = false;
+                        None
+                    }
+                }
+            } else {
+                match keys.recv_timeout(self.delay) {
+                    Ok(key) => Some(key),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        thread::sleep(self.delay);
+                        None
+                    }
+                }
+            };
+            let advance = match key {
+                Some(b'q') => break,
+                Some(b' ') => {
+                    paused = !paused;
+                    false
+                }
+                Some(b'n') => paused,
+                Some(b'+') => {
+                    self.faster();
+                    false
+                }
+                Some(b'-') => {
+                    self.slower();
+                    false
+                }
+                Some(_) => false,
+                None => !paused,
+            };
+            if advance {
+                let next = (&mut frames).take(self.stride).last();
+                match next {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+        }
+        println!();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump() {
+        let frames: Frames = Box::new([("start", "..#"), ("end", ".o#")].into_iter().map(
+            |(caption, row)| Frame {
+                caption: caption.to_string(),
+                rows: vec![row.to_string()],
+            },
+        ));
+        let mut out = Vec::new();
+        assert_eq!(dump(frames, &mut out).unwrap(), 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "== start ==\n..#\n\n== end ==\n.o#\n\n"
+        );
+        assert_eq!(colourize(".o#.", 3), ".\x1b[33mo\x1b[90m#\x1b[0m");
+    }
+}
@@ -0,0 +1,212 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+// What tells whether a file changed: its modification time and length, or
+// `None` while it does not exist.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &PathBuf) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Polls files for changes, including their creation and removal.
+#[derive(Debug)]
+pub struct Watcher {
+    files: Vec<(PathBuf, Stamp)>,
+}
+
+impl Watcher {
+    /// Watch the given files, taking their current state as unchanged.
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Watcher {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let stamp = stamp(&path);
+                (path, stamp)
+            })
+            .collect();
+        Watcher { files }
+    }
+
+    /// Return the files changed since the last call.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, last) in &mut self.files {
+            let current = stamp(path);
+            if current != *last {
+                *last = current;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+/// Return the lines reporting the result of a run, e.g. an answer, compared
+/// with the result of the previous run if any. A changed single-line result
+/// shows both, and a changed multi-line result shows the lines that differ
+/// prefixed with `-` and `+`.
+pub fn diff(label: &str, result: &str, previous: Option<&str>) -> String {
+    let Some(previous) = previous else {
+        return if result.contains('\n') {
+            format!("{}:\n{}", label, result)
+        } else {
+            format!("{}: {}", label, result)
+        };
+    };
+    if previous == result {
+        return if result.contains('\n') {
+            format!("{}: unchanged\n{}", label, result)
+        } else {
+            format!("{}: {} (unchanged)", label, result)
+        };
+    }
+    if !previous.contains('\n') && !result.contains('\n') {
+        return format!("{}: {} -> {}", label, previous, result);
+    }
+
+    let mut lines = vec![format!("{}: changed", label)];
+    let (old, new): (Vec<&str>, Vec<&str>) = (previous.lines().collect(), result.lines().collect());
+    for i in 0..old.len().max(new.len()) {
+        match (old.get(i), new.get(i)) {
+            (Some(o), Some(n)) if o == n => lines.push(format!("  {}", n)),
+            (o, n) => {
+                lines.extend(o.map(|o| format!("- {}", o)));
+                lines.extend(n.map(|n| format!("+ {}", n)));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Names of the unit tests of a day that passed and failed, such as the tests
+/// checking the answers to its examples.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct TestResults {
+    pub passed: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+impl TestResults {
+    /// Summarize the results as a single line, naming the failed tests.
+    pub fn summary(&self) -> String {
+        if self.failed.is_empty() {
+            format!("{} passed", self.passed.len())
+        } else {
+            format!(
+                "{} of {} failed: {}",
+                self.failed.len(),
+                self.passed.len() + self.failed.len(),
+                self.failed.join(", ")
+            )
+        }
+    }
+}
+
+// Collect the results the test harness printed for the tests of the given day,
+// e.g. `test day03::tests::test_part1 ... ok`.
+fn parse_test_output(s: &str, day: u8) -> TestResults {
+    let prefix = format!("day{:02}::tests::", day);
+    let mut results = TestResults::default();
+    for line in s.lines() {
+        let Some((name, result)) = line
+            .strip_prefix("test ")
+            .and_then(|line| line.split_once(" ... "))
+        else {
+            continue;
+        };
+        let name = name.strip_prefix(&prefix).unwrap_or(name).to_string();
+        match result {
+            "ok" => results.passed.push(name),
+            "FAILED" => results.failed.push(name),
+            _ => {}
+        }
+    }
+    results
+}
+
+/// Build and run the unit tests of a day with cargo in the crate at the given
+/// directory, so that they check the latest source. Returns the compiler
+/// errors if the tests do not build.
+pub fn run_tests(crate_dir: &Path, day: u8) -> Result<TestResults, String> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .current_dir(crate_dir)
+        .args([
+            "test",
+            "--lib",
+            &format!("day{:02}::", day),
+            "--",
+            "--color",
+            "never",
+        ])
+        .output()
+        .map_err(|e| format!("failed to run cargo: {}", e))?;
+    let results = parse_test_output(&String::from_utf8_lossy(&output.stdout), day);
+    if !output.status.success() && results.failed.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let errors: Vec<&str> = stderr.lines().filter(|l| l.starts_with("error")).collect();
+        return Err(if errors.is_empty() {
+            String::from("cargo test failed")
+        } else {
+            errors.join("\n")
+        });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watcher() {
+        let path = env::temp_dir().join(format!("aoc22-watch-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut watcher = Watcher::new([path.clone()]);
+        assert!(watcher.changed().is_empty());
+        fs::write(&path, "1\n").unwrap();
+        assert_eq!(watcher.changed(), vec![path.clone()]);
+        assert!(watcher.changed().is_empty());
+        // A different length counts as a change even within the resolution of
+        // modification times.
+        fs::write(&path, "12\n").unwrap();
+        assert_eq!(watcher.changed(), vec![path.clone()]);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.changed(), vec![path]);
+
+        assert_eq!(diff("day01a", "24000", None), "day01a: 24000");
+        assert_eq!(
+            diff("day01a", "24000", Some("24000")),
+            "day01a: 24000 (unchanged)"
+        );
+        assert_eq!(
+            diff("day01a", "45000", Some("24000")),
+            "day01a: 24000 -> 45000"
+        );
+        assert_eq!(
+            diff("day10b", "##..\n#..#", Some("##..\n#.##")),
+            "day10b: changed\n  ##..\n- #.##\n+ #..#"
+        );
+    }
+
+    #[test]
+    fn test_parse_test_output() {
+        let output = "
+running 3 tests
+test day03::tests::test_common_items ... ok
+test day03::tests::test_part1 ... FAILED
+test day03::tests::test_slow ... ignored
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 84 filtered out";
+        let results = parse_test_output(output, 3);
+        assert_eq!(results.passed, vec!["test_common_items"]);
+        assert_eq!(results.failed, vec!["test_part1"]);
+        assert_eq!(results.summary(), "1 of 2 failed: test_part1");
+        assert_eq!(TestResults::default().summary(), "0 passed");
+    }
+}